@@ -1,7 +1,7 @@
-use std::{f32::consts::E, iter::Peekable, sync::Arc};
+use std::iter::Peekable;
 
 use proc_macro2::{
-    token_stream::IntoIter, Delimiter, Group, Punct, Spacing, Span, TokenStream, TokenTree,
+    token_stream::IntoIter, Delimiter, Group, Spacing, Span, TokenStream, TokenTree,
 };
 use quote::{quote, ToTokens, TokenStreamExt};
 
@@ -11,8 +11,8 @@ use crate::node::ChimeraMacroNode;
 pub fn chimera(input: TokenStream) -> TokenStream {
     let iter = &mut input.into_iter().peekable();
 
-    match parse_node(iter) {
-        Ok(node) => node.to_token_stream().into(),
+    match parse_root(iter) {
+        Ok(node) => node.to_token_stream(),
         Err(ParseError::EndOfInput { expected }) => {
             panic!("Unexpected end of input, expected {}", expected)
         }
@@ -53,6 +53,19 @@ enum ParseError {
     },
 }
 
+/// Parse the top level of the template. Several root nodes are wrapped in a fragment
+fn parse_root(iter: &mut Peekable<IntoIter>) -> Result<ChimeraMacroNode, ParseError> {
+    let mut nodes = vec![];
+    while let Some(node) = parse_node(iter)? {
+        nodes.push(node);
+    }
+    if nodes.len() == 1 {
+        Ok(nodes.remove(0))
+    } else {
+        Ok(ChimeraMacroNode::Fragment { child_nodes: nodes })
+    }
+}
+
 /// Take a single token and return failure if it isn't what we expected
 fn take_token(iter: &mut Peekable<IntoIter>, expected: String) -> Result<TokenTree, ParseError> {
     iter.next().ok_or(ParseError::EndOfInput { expected })
@@ -89,13 +102,27 @@ fn parse_punct(input: &TokenTree, punct: char) -> Result<(), ParseError> {
 
 /// Parse a node, which takes the form of:
 /// - an entity tag: <tag component1 component2>child</tag> or "text"
-/// - an if node
+/// - a fragment of several nodes: <>children</>
+/// - an expression: {value}, rendered as text
+/// - an if node, optionally followed by #else if / #else branches
+/// - a for node: #for item in iterator { children }
+/// - a match node: #match value { Pattern => { children }, ... }
+/// - a user defined component: <Component property="value">children</Component>
+/// - a splice of already built nodes: #{nodes}
 fn parse_node(iter: &mut Peekable<IntoIter>) -> Result<Option<ChimeraMacroNode>, ParseError> {
-    let token: Result<TokenTree, ParseError>;
-    token = peek_token(iter, "<components>, #if, or text".to_string());
+    let token = peek_token(iter, "<components>, #if, {expression} or text".to_string());
     match token.clone() {
-        Ok(TokenTree::Punct(p)) if p.as_char() == '<' => parse_entity_tag(iter).map(Some),
-        Ok(TokenTree::Punct(p)) if p.as_char() == '#' => parse_if(iter).map(Some),
+        Ok(TokenTree::Punct(p)) if p.as_char() == '<' => {
+            let (node, end) = parse_entity_tag(iter)?;
+            Ok(Some(space_after_element(iter, node, end)))
+        }
+        Ok(TokenTree::Punct(p)) if p.as_char() == '#' => parse_directive(iter).map(Some),
+        Ok(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => {
+            parse_expression(iter).map(Some)
+        }
+        Ok(TokenTree::Group(g)) if contains_expression(g.stream()) => {
+            parse_text_group(iter).map(Some)
+        }
         Ok(_) => parse_text(iter).map(Some),
         Err(ParseError::EndOfInput { expected: _ }) => {
             iter.next();
@@ -109,78 +136,285 @@ fn parse_node(iter: &mut Peekable<IntoIter>) -> Result<Option<ChimeraMacroNode>,
     }
 }
 
+/// Keep a gap between an element and the inline content after it as a single space,
+/// such as in `<b>{name}</b> said`
+fn space_after_element(
+    iter: &mut Peekable<IntoIter>,
+    node: ChimeraMacroNode,
+    end: Span,
+) -> ChimeraMacroNode {
+    if !spaced_before_inline(iter, end)
+        || matches!(iter.peek(), Some(TokenTree::Punct(p)) if p.as_char() == '<')
+    {
+        return node;
+    }
+    ChimeraMacroNode::Fragment {
+        child_nodes: vec![node, text_node(" ")],
+    }
+}
+
+/// Parse literal text up until the next tag, directive or {expression}
 fn parse_text(iter: &mut Peekable<IntoIter>) -> Result<ChimeraMacroNode, ParseError> {
     let mut text = String::new();
+    let mut last_span: Option<Span> = None;
     loop {
-        let token = peek_token(iter, "text or <".to_string())?;
-        match token {
-            TokenTree::Punct(p) if p.as_char() == '<' => {
-                break;
-            }
-            t => {
-                let token = take_token(iter, "text".to_string())?;
-                text.push_str(&token.to_string());
+        match iter.peek().cloned() {
+            None => break,
+            Some(TokenTree::Punct(p)) if p.as_char() == '<' => break,
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => break,
+            Some(TokenTree::Group(g)) if contains_expression(g.stream()) => break,
+            Some(TokenTree::Punct(p)) if p.as_char() == '#' && peek_directive(iter) => break,
+            Some(token) => {
+                if last_span.is_some_and(|last| is_spaced(last, token.span())) {
+                    text.push(' ');
+                }
+                last_span = Some(token.span());
+                text.push_str(&take_token(iter, "text".to_string())?.to_string());
             }
         }
     }
-    Ok(ChimeraMacroNode::Entity {
+    if last_span.is_some_and(|last| spaced_before_inline(iter, last)) {
+        text.push(' ');
+    }
+    Ok(text_node(&text))
+}
+
+/// An entity node with just a Text component
+fn text_node(text: &str) -> ChimeraMacroNode {
+    ChimeraMacroNode::Entity {
         components: vec![quote!(chimera_rs::html::Text(#text.to_string()))],
-        child_nodes: Arc::new(vec![]),
+        child_nodes: vec![],
+    }
+}
+
+/// Parse a (group) or [group] in text position which contains {expressions},
+/// into a fragment of its delimiters and inner nodes
+fn parse_text_group(iter: &mut Peekable<IntoIter>) -> Result<ChimeraMacroNode, ParseError> {
+    let group = match take_token(iter, "(text)".to_string())? {
+        TokenTree::Group(g) => g,
+        t => {
+            return Err(ParseError::UnexpectedToken {
+                expected: "(text)".to_string(),
+                found: t.to_string(),
+                at: t.span(),
+            })
+        }
+    };
+    let (open, close) = match group.delimiter() {
+        Delimiter::Bracket => ("[", "]"),
+        _ => ("(", ")"),
+    };
+    let close = if spaced_before_inline(iter, group.span_close()) {
+        format!("{close} ")
+    } else {
+        close.to_string()
+    };
+    let mut child_nodes = vec![text_node(open)];
+    child_nodes.extend(parse_group_children(&group)?);
+    child_nodes.push(text_node(&close));
+    Ok(ChimeraMacroNode::Fragment { child_nodes })
+}
+
+/// Whether a token stream contains an {expression} at any depth
+fn contains_expression(stream: TokenStream) -> bool {
+    stream.into_iter().any(|token| match token {
+        TokenTree::Group(g) => g.delimiter() == Delimiter::Brace || contains_expression(g.stream()),
+        _ => false,
     })
 }
 
+/// Parse a {expression} in text position, which is rendered through its Display impl
+fn parse_expression(iter: &mut Peekable<IntoIter>) -> Result<ChimeraMacroNode, ParseError> {
+    let token = take_token(iter, "{expression}".to_string())?;
+    let expression = match &token {
+        TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => g.stream(),
+        t => {
+            return Err(ParseError::UnexpectedToken {
+                expected: "{expression}".to_string(),
+                found: t.to_string(),
+                at: t.span(),
+            })
+        }
+    };
+    let format = if spaced_before_inline(iter, token.span()) {
+        "{} "
+    } else {
+        "{}"
+    };
+    Ok(ChimeraMacroNode::Entity {
+        components: vec![quote!(chimera_rs::html::Text(
+            format!(#format, #expression)
+        ))],
+        child_nodes: vec![],
+    })
+}
+
+/// Peek at upcoming tokens, return true if it's a directive such as #if or #{nodes}
+fn peek_directive(iter: &Peekable<IntoIter>) -> bool {
+    let cloned_iter = &mut iter.clone();
+    cloned_iter.next();
+    match cloned_iter.next() {
+        Some(TokenTree::Ident(_)) => true,
+        Some(TokenTree::Group(g)) => g.delimiter() == Delimiter::Brace,
+        _ => false,
+    }
+}
+
+/// Whether there was whitespace in the source between two tokens
+fn is_spaced(previous: Span, next: Span) -> bool {
+    previous.unwrap().byte_range().end < next.unwrap().byte_range().start
+}
+
+/// Whether the next token continues inline content (text, an {expression} or an element)
+/// after a gap, in which case the preceding segment owns the separating space.
+/// Close tags end the content, so there's no space before them
+fn spaced_before_inline(iter: &mut Peekable<IntoIter>, previous: Span) -> bool {
+    match iter.peek().cloned() {
+        None => false,
+        Some(TokenTree::Punct(p)) if p.as_char() == '<' && peek_end_tag(iter).is_ok() => false,
+        Some(TokenTree::Punct(p)) if p.as_char() == '#' && peek_directive(iter) => false,
+        Some(token) => is_spaced(previous, token.span()),
+    }
+}
+
 /// Parse a  tag name
 fn parse_tag_element(iter: &mut Peekable<IntoIter>) -> Result<String, ParseError> {
-    let mut tag: Option<String> = None;
-    let mut joint_to_continue_name = false;
-    loop {
-        let input = peek_token(iter, "tag name".to_string())?;
-        match (input, &mut tag) {
-            (TokenTree::Ident(i), None) => {
-                tag = Some(i.to_string());
-                joint_to_continue_name = true;
-                take_token(iter, "tag name".to_string())?;
-                Ok(())
-            }
-            (TokenTree::Ident(i), Some(t)) => {
-                //We got an ident instead of a joint, we've gone past the tag name
-                if joint_to_continue_name {
-                    break;
-                }
-                t.push_str(&i.to_string());
-                Ok(())
-            }
-            (TokenTree::Punct(p), Some(t))
-                if p.as_char() == '-' && p.spacing() == Spacing::Joint =>
-            {
-                t.push_str("-");
-                joint_to_continue_name = false;
-                Ok(())
-            }
-            (TokenTree::Punct(p), Some(_t)) if p.as_char() == '/' || p.as_char() == '>' => {
-                break;
-            }
-            (TokenTree::Punct(p), None) => Err(ParseError::UnexpectedToken {
-                expected: "identifier".to_string(),
-                found: p.to_string(),
-                at: p.span(),
-            }),
-            (t, _) => Err(ParseError::UnexpectedToken {
+    parse_dashed_name(iter, "tag name").map(|(name, _)| name)
+}
+
+/// Parse a name which may be joined by dashes, such as a custom element or data-* attribute.
+/// Returns the name and the span of its last token
+fn parse_dashed_name(
+    iter: &mut Peekable<IntoIter>,
+    expected: &str,
+) -> Result<(String, Span), ParseError> {
+    let (mut name, mut span) = match take_token(iter, expected.to_string())? {
+        TokenTree::Ident(i) => (i.to_string(), i.span()),
+        t => {
+            return Err(ParseError::UnexpectedToken {
                 expected: "identifier".to_string(),
                 found: t.to_string(),
                 at: t.span(),
-            }),
-        }?;
+            })
+        }
+    };
+    loop {
+        let cloned_iter = &mut iter.clone();
+        match (cloned_iter.next(), cloned_iter.next()) {
+            (Some(TokenTree::Punct(p)), Some(TokenTree::Ident(i)))
+                if p.as_char() == '-'
+                    && !is_spaced(span, p.span())
+                    && !is_spaced(p.span(), i.span()) =>
+            {
+                name.push('-');
+                name.push_str(&i.to_string());
+                span = i.span();
+                iter.next();
+                iter.next();
+            }
+            _ => break,
+        }
     }
+    Ok((name, span))
+}
+
+/// Peek at upcoming tokens, return true if it's a name="value" attribute rather than a component
+fn peek_attribute(iter: &Peekable<IntoIter>) -> bool {
+    let cloned_iter = &mut iter.clone();
+    parse_dashed_name(cloned_iter, "attribute name").is_ok()
+        && matches!(cloned_iter.next(), Some(TokenTree::Punct(p)) if p.as_char() == '=')
+}
+
+/// Elements which can't have content, so are written without a close tag like <br>.
+/// Matches VOID_ELEMENTS in chimera-rs's html renderer
+static VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
 
-    match tag {
-        Some(t) => Ok(t),
-        None => Err(ParseError::EndOfInput {
-            expected: "tag name".to_string(),
+/// Peek for a bare attribute like <input disabled>. Components are types, so start uppercase,
+/// while attributes are lowercase and not followed by a struct initializer or :: path
+fn peek_boolean_attribute(iter: &Peekable<IntoIter>) -> bool {
+    let cloned_iter = &mut iter.clone();
+    if !matches!(cloned_iter.peek(), Some(TokenTree::Ident(i)) if i.to_string().starts_with(char::is_lowercase))
+    {
+        return false;
+    }
+    if parse_dashed_name(cloned_iter, "attribute name").is_err() {
+        return false;
+    }
+    match cloned_iter.next() {
+        Some(TokenTree::Group(_)) => false,
+        Some(TokenTree::Punct(p)) if p.as_char() == ':' => {
+            !matches!(cloned_iter.next(), Some(TokenTree::Punct(p)) if p.as_char() == ':')
+        }
+        _ => true,
+    }
+}
+
+/// Peek for a class:name={condition} toggle
+fn peek_class_toggle(iter: &Peekable<IntoIter>) -> bool {
+    let cloned_iter = &mut iter.clone();
+    matches!(cloned_iter.next(), Some(TokenTree::Ident(i)) if i == "class")
+        && matches!(cloned_iter.next(), Some(TokenTree::Punct(p)) if p.as_char() == ':')
+}
+
+/// Parse a class:name={condition} toggle, returning the class name and condition
+fn parse_class_toggle(iter: &mut Peekable<IntoIter>) -> Result<(String, TokenStream), ParseError> {
+    iter.next();
+    parse_punct(&take_token(iter, ':'.to_string())?, ':')?;
+    let (name, _) = parse_dashed_name(iter, "class name")?;
+    parse_punct(&take_token(iter, '='.to_string())?, '=')?;
+    match take_token(iter, "{condition}".to_string())? {
+        TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => Ok((name, g.stream())),
+        v => Err(ParseError::UnexpectedToken {
+            expected: "{condition}".to_string(),
+            found: v.to_string(),
+            at: v.span(),
         }),
     }
 }
 
+/// Parse a name="value" or name={expression} attribute, returning the name, the span of the
+/// name and the value token
+fn parse_attribute(iter: &mut Peekable<IntoIter>) -> Result<(String, Span, TokenTree), ParseError> {
+    let (name, span) = parse_dashed_name(iter, "attribute name")?;
+    parse_punct(&take_token(iter, '='.to_string())?, '=')?;
+    let value = take_token(iter, "attribute value".to_string())?;
+    match &value {
+        TokenTree::Literal(_) => Ok((name, span, value)),
+        TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => Ok((name, span, value)),
+        v => Err(ParseError::UnexpectedToken {
+            expected: "attribute value literal or {expression}".to_string(),
+            found: v.to_string(),
+            at: v.span(),
+        }),
+    }
+}
+
+/// Value of an html attribute, which is always a string
+fn attribute_value_string(value: &TokenTree) -> TokenStream {
+    match value {
+        TokenTree::Group(g) => {
+            let expression = g.stream();
+            quote!(::std::string::ToString::to_string(&(#expression)))
+        }
+        literal => quote!(#literal.to_string()),
+    }
+}
+
+/// Value of a component property. Expressions are used as is, while string literals are
+/// converted so they can be assigned to String properties
+fn property_value(value: &TokenTree) -> TokenStream {
+    match value {
+        TokenTree::Group(g) => g.stream(),
+        TokenTree::Literal(l) if l.to_string().starts_with('"') => {
+            quote!(::std::convert::Into::into(#l))
+        }
+        literal => literal.clone().into(),
+    }
+}
+
 /// Peek at upcoming tokens, return success if its self closing tag '/>'
 fn peek_self_close_tag(iter: &mut Peekable<IntoIter>) -> Result<(), ParseError> {
     let cloned_iter = &mut iter.clone();
@@ -190,12 +424,13 @@ fn peek_self_close_tag(iter: &mut Peekable<IntoIter>) -> Result<(), ParseError>
     Ok(())
 }
 
-/// Parse upcoming tokens, return success if its self closing tag '/>'
-fn parse_self_close_tag(iter: &mut Peekable<IntoIter>) -> Result<(), ParseError> {
+/// Parse upcoming tokens, return the span of its end if its self closing tag '/>'
+fn parse_self_close_tag(iter: &mut Peekable<IntoIter>) -> Result<Span, ParseError> {
     parse_punct(&take_token(iter, '/'.to_string())?, '/')?;
     //Parse closing tag slash
-    parse_punct(&take_token(iter, '>'.to_string())?, '>')?;
-    Ok(())
+    let end = take_token(iter, '>'.to_string())?;
+    parse_punct(&end, '>')?;
+    Ok(end.span())
 }
 
 /// Peek at upcoming tokens, return success if its beginning of end tag '</'
@@ -216,39 +451,147 @@ fn capitalize(s: &str) -> String {
 }
 
 /// Parse components of an entity node, which is a space seperated list of struct initializers
-fn parse_entity_tag(iter: &mut Peekable<IntoIter>) -> Result<ChimeraMacroNode, ParseError> {
+/// and name="value" attributes. Attributes are collected into a single Attributes component.
+/// Uppercase tags are user defined components instead.
+/// Returns the node and the span of its last '>'
+fn parse_entity_tag(iter: &mut Peekable<IntoIter>) -> Result<(ChimeraMacroNode, Span), ParseError> {
     parse_punct(&take_token(iter, '<'.to_string())?, '<')?;
-    let element = parse_tag_element(iter)?;
+    if matches!(iter.peek(), Some(TokenTree::Punct(p)) if p.as_char() == '>') {
+        iter.next();
+        let (child_nodes, end) = parse_children_and_close_tag(iter, String::new())?;
+        return Ok((ChimeraMacroNode::Fragment { child_nodes }, end));
+    }
+    let (element, element_span) = parse_dashed_name(iter, "tag name")?;
+    if element.starts_with(char::is_uppercase) {
+        return parse_component_tag(iter, element, element_span);
+    }
     let primary_component = TokenTree::Ident(proc_macro2::Ident::new(
-        &capitalize(&element).replace("-", "_").as_str(),
+        capitalize(&element).replace('-', "_").as_str(),
         proc_macro2::Span::call_site(),
     ));
     let mut components_tokens: Vec<TokenStream> =
         vec![quote! { Tag(#element) }, primary_component.into()];
-    loop {
+    let mut attributes: Vec<(String, Span, TokenTree)> = vec![];
+    let mut class_toggles: Vec<(String, TokenStream)> = vec![];
+    let (self_closing, open_end) = loop {
         if peek_self_close_tag(iter).is_ok() {
-            parse_self_close_tag(iter)?;
-            break;
+            break (true, parse_self_close_tag(iter)?);
         }
-        let token = take_token(iter, "component or >".to_string())?;
+        if peek_class_toggle(iter) {
+            class_toggles.push(parse_class_toggle(iter)?);
+            continue;
+        }
+        if peek_attribute(iter) {
+            attributes.push(parse_attribute(iter)?);
+            continue;
+        }
+        if peek_boolean_attribute(iter) {
+            let (name, span) = parse_dashed_name(iter, "boolean attribute")?;
+            let value = TokenTree::Literal(proc_macro2::Literal::string(""));
+            attributes.push((name, span, value));
+            continue;
+        }
+        let token = take_token(iter, "component, attribute or >".to_string())?;
         match token.clone() {
-            TokenTree::Punct(p) if p.as_char() == '>' => break,
+            TokenTree::Punct(p) if p.as_char() == '>' => break (false, p.span()),
             TokenTree::Ident(_) => {
                 let mut stream = TokenStream::from(token);
                 //Check if its a group next, it would be a struct initializer, if so put that alongside
                 let next = peek_token(iter, "next component".to_string())?;
-                match next {
-                    TokenTree::Group(_) => {
-                        stream.append(next);
-                        iter.next();
-                    }
-                    _ => {}
+                if let TokenTree::Group(_) = next {
+                    stream.append(next);
+                    iter.next();
                 }
                 components_tokens.push(stream);
             }
             _ => {}
         }
+    };
+    if !attributes.is_empty() {
+        let attributes = attributes.iter().map(|(name, _, value)| {
+            let value = attribute_value_string(value);
+            quote!((#name.to_string(), #value))
+        });
+        components_tokens.push(quote! {
+            chimera_rs::html::attributes::Attributes(chimera_rs::html::attributes::AttributeMap::from([
+                #(#attributes),*
+            ]))
+        });
     }
+    if !class_toggles.is_empty() {
+        let classes = class_toggles
+            .iter()
+            .map(|(name, condition)| quote!((::std::borrow::Cow::from(#name), #condition)));
+        components_tokens.push(quote! {
+            chimera_rs::html::classes::Classes(vec![#(#classes),*])
+        });
+    }
+    let (child_nodes, end) = if self_closing || VOID_ELEMENTS.contains(&element.as_str()) {
+        (vec![], open_end)
+    } else {
+        parse_children_and_close_tag(iter, element)?
+    };
+    let node = ChimeraMacroNode::Entity {
+        components: components_tokens,
+        child_nodes,
+    };
+    Ok((node, end))
+}
+
+/// Parse a user defined component tag, whose attributes are the fields of the component struct
+fn parse_component_tag(
+    iter: &mut Peekable<IntoIter>,
+    element: String,
+    element_span: Span,
+) -> Result<(ChimeraMacroNode, Span), ParseError> {
+    let name = ident_for(&element, element_span)?;
+    let mut properties = vec![];
+    let self_close_end = loop {
+        if peek_self_close_tag(iter).is_ok() {
+            break Some(parse_self_close_tag(iter)?);
+        }
+        let token = peek_token(iter, "property or >".to_string())?;
+        match &token {
+            TokenTree::Punct(p) if p.as_char() == '>' => {
+                iter.next();
+                break None;
+            }
+            _ => {
+                let (property, span, value) = parse_attribute(iter)?;
+                properties.push((ident_for(&property, span)?, property_value(&value)));
+            }
+        }
+    };
+    let (child_nodes, end) = match self_close_end {
+        Some(end) => (vec![], end),
+        None => parse_children_and_close_tag(iter, element)?,
+    };
+    let node = ChimeraMacroNode::Component {
+        name,
+        properties,
+        child_nodes,
+    };
+    Ok((node, end))
+}
+
+/// Make an identifier out of a tag or property name, which fails if the name has dashes
+fn ident_for(name: &str, span: Span) -> Result<proc_macro2::Ident, ParseError> {
+    if name.contains('-') {
+        return Err(ParseError::UnexpectedToken {
+            expected: "identifier".to_string(),
+            found: name.to_string(),
+            at: span,
+        });
+    }
+    Ok(proc_macro2::Ident::new(name, span))
+}
+
+/// Parse child nodes up until the closing tag, then ensure the closing tag matches the element.
+/// Fragments have an empty element name. Returns the children and the span of the closing '>'
+fn parse_children_and_close_tag(
+    iter: &mut Peekable<IntoIter>,
+    element: String,
+) -> Result<(Vec<ChimeraMacroNode>, Span), ParseError> {
     let mut child_nodes = vec![];
 
     loop {
@@ -269,7 +612,11 @@ fn parse_entity_tag(iter: &mut Peekable<IntoIter>) -> Result<ChimeraMacroNode, P
     parse_punct(&take_token(iter, '/'.to_string())?, '/')?;
     //Ensure closing tag name matches opening tag name
     let close_token = peek_token(iter, "tag name".to_string())?;
-    let close_tag = parse_tag_element(iter)?;
+    let close_tag = match &close_token {
+        //Fragments close with </>
+        TokenTree::Punct(p) if p.as_char() == '>' => String::new(),
+        _ => parse_tag_element(iter)?,
+    };
     if close_tag != element {
         return Err(ParseError::InvalidClose {
             open: element,
@@ -278,44 +625,147 @@ fn parse_entity_tag(iter: &mut Peekable<IntoIter>) -> Result<ChimeraMacroNode, P
         });
     }
     //Parse closing tag closing bracket
-    parse_punct(&take_token(iter, '>'.to_string())?, '>')?;
-
-    Ok(ChimeraMacroNode::Entity {
-        components: components_tokens,
-        child_nodes: Arc::new(child_nodes),
-    })
+    let end = take_token(iter, '>'.to_string())?;
+    parse_punct(&end, '>')?;
+    Ok((child_nodes, end.span()))
 }
 
-fn parse_if(iter: &mut Peekable<IntoIter>) -> Result<ChimeraMacroNode, ParseError> {
+/// Parse a # directive, which is an if, for or match node, or a #{nodes} splice
+fn parse_directive(iter: &mut Peekable<IntoIter>) -> Result<ChimeraMacroNode, ParseError> {
     parse_punct(&take_token(iter, '#'.to_string())?, '#')?;
-    let token = take_token(iter, "if".to_string())?;
+    let token = take_token(iter, "if, for, match or {nodes}".to_string())?;
     match token {
-        TokenTree::Ident(i) if i.to_string() == "if" => {
-            let (condition, inner_tree) = parse_if_inner(iter)?;
-            let mut inner = inner_tree.stream().into_iter().peekable();
-            let mut child_nodes = vec![];
-            loop {
-                let child = parse_node(&mut inner)?;
-                if let Some(child) = child {
-                    child_nodes.push(child);
-                } else {
-                    break;
-                }
-            }
-            Ok(ChimeraMacroNode::If {
-                condition,
-                child_nodes: Arc::new(child_nodes),
-            })
+        TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => {
+            Ok(ChimeraMacroNode::Splice { nodes: g.stream() })
         }
+        TokenTree::Ident(i) if i == "if" => parse_if_branches(iter),
+        TokenTree::Ident(i) if i == "for" => parse_for(iter),
+        TokenTree::Ident(i) if i == "match" => parse_match(iter),
         _ => Err(ParseError::UnexpectedToken {
-            expected: "if".to_string(),
+            expected: "if, for, match or {nodes}".to_string(),
             found: token.to_string(),
             at: token.span(),
         }),
     }
 }
 
-/// Return the condition and inner tokens of an if statement
+/// Parse the condition and children of an if node after the `if` keyword, then any else branch
+fn parse_if_branches(iter: &mut Peekable<IntoIter>) -> Result<ChimeraMacroNode, ParseError> {
+    let (condition, inner_tree) = parse_if_inner(iter)?;
+    let child_nodes = parse_group_children(&inner_tree)?;
+    let else_child_nodes = if peek_else(iter) {
+        //Skip past #else
+        iter.next();
+        iter.next();
+        let token = peek_token(iter, "if or { children }".to_string())?;
+        match &token {
+            TokenTree::Ident(i) if i == "if" => {
+                iter.next();
+                vec![parse_if_branches(iter)?]
+            }
+            TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => {
+                iter.next();
+                parse_group_children(g)?
+            }
+            t => {
+                return Err(ParseError::UnexpectedToken {
+                    expected: "if or { children }".to_string(),
+                    found: t.to_string(),
+                    at: t.span(),
+                })
+            }
+        }
+    } else {
+        vec![]
+    };
+    Ok(ChimeraMacroNode::If {
+        condition,
+        child_nodes,
+        else_child_nodes,
+    })
+}
+
+/// Parse the pattern, iterator and children of a for node after the `for` keyword
+fn parse_for(iter: &mut Peekable<IntoIter>) -> Result<ChimeraMacroNode, ParseError> {
+    let mut pattern = TokenStream::new();
+    loop {
+        let token = take_token(iter, "pattern, or in".to_string())?;
+        match &token {
+            TokenTree::Ident(i) if i == "in" => break,
+            _ => pattern.append(token),
+        }
+    }
+    let (iterator, inner_tree) = parse_if_inner(iter)?;
+    Ok(ChimeraMacroNode::For {
+        pattern,
+        iterator,
+        child_nodes: parse_group_children(&inner_tree)?,
+    })
+}
+
+/// Parse the value and arms of a match node after the `match` keyword
+fn parse_match(iter: &mut Peekable<IntoIter>) -> Result<ChimeraMacroNode, ParseError> {
+    let (value, arms_tree) = parse_if_inner(iter)?;
+    let arms_iter = &mut arms_tree.stream().into_iter().peekable();
+    let mut arms = vec![];
+    while arms_iter.peek().is_some() {
+        arms.push(parse_match_arm(arms_iter)?);
+    }
+    Ok(ChimeraMacroNode::Match { value, arms })
+}
+
+/// Parse a single `Pattern => { children }` match arm, with an optional trailing comma.
+/// The pattern includes any `if` guard
+fn parse_match_arm(
+    iter: &mut Peekable<IntoIter>,
+) -> Result<(TokenStream, Vec<ChimeraMacroNode>), ParseError> {
+    let mut pattern = TokenStream::new();
+    loop {
+        let token = take_token(iter, "pattern, or =>".to_string())?;
+        match &token {
+            TokenTree::Punct(p) if p.as_char() == '=' && p.spacing() == Spacing::Joint => {
+                parse_punct(&take_token(iter, '>'.to_string())?, '>')?;
+                break;
+            }
+            _ => pattern.append(token),
+        }
+    }
+    let token = take_token(iter, "{ children }".to_string())?;
+    let child_nodes = match &token {
+        TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => parse_group_children(g)?,
+        t => {
+            return Err(ParseError::UnexpectedToken {
+                expected: "{ children }".to_string(),
+                found: t.to_string(),
+                at: t.span(),
+            })
+        }
+    };
+    if matches!(iter.peek(), Some(TokenTree::Punct(p)) if p.as_char() == ',') {
+        iter.next();
+    }
+    Ok((pattern, child_nodes))
+}
+
+/// Peek at upcoming tokens, return true if it's an #else branch
+fn peek_else(iter: &Peekable<IntoIter>) -> bool {
+    let cloned_iter = &mut iter.clone();
+    matches!(cloned_iter.next(), Some(TokenTree::Punct(p)) if p.as_char() == '#')
+        && matches!(cloned_iter.next(), Some(TokenTree::Ident(i)) if i == "else")
+}
+
+/// Parse all the nodes inside a { children } group
+fn parse_group_children(group: &Group) -> Result<Vec<ChimeraMacroNode>, ParseError> {
+    let mut inner = group.stream().into_iter().peekable();
+    let mut child_nodes = vec![];
+    while let Some(child) = parse_node(&mut inner)? {
+        child_nodes.push(child);
+    }
+    Ok(child_nodes)
+}
+
+/// Return the condition and inner tokens of an if statement, or the iterator of a for,
+/// or the value of a match
 fn parse_if_inner(iter: &mut Peekable<IntoIter>) -> Result<(TokenStream, Group), ParseError> {
     let mut condition_tokens: TokenStream = TokenStream::new();
     loop {
@@ -330,3 +780,20 @@ fn parse_if_inner(iter: &mut Peekable<IntoIter>) -> Result<(TokenStream, Group),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::VOID_ELEMENTS;
+
+    #[test]
+    fn void_elements_match_the_renderer() {
+        let render = include_str!("../../chimera-rs/src/html/render.rs");
+        let list = render
+            .split("static VOID_ELEMENTS")
+            .nth(1)
+            .and_then(|rest| rest.split("];").next())
+            .expect("render.rs should declare VOID_ELEMENTS");
+        let elements = list.split('"').skip(1).step_by(2).collect::<Vec<_>>();
+        assert_eq!(elements, VOID_ELEMENTS);
+    }
+}
@@ -1,17 +1,36 @@
-use std::sync::Arc;
-
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, TokenStream};
 use quote::{quote, ToTokens};
 
 #[derive(Debug, Clone)]
 pub enum ChimeraMacroNode {
     Entity {
         components: Vec<TokenStream>,
-        child_nodes: Arc<Vec<ChimeraMacroNode>>,
+        child_nodes: Vec<ChimeraMacroNode>,
     },
     If {
         condition: TokenStream,
-        child_nodes: Arc<Vec<ChimeraMacroNode>>,
+        child_nodes: Vec<ChimeraMacroNode>,
+        else_child_nodes: Vec<ChimeraMacroNode>,
+    },
+    For {
+        pattern: TokenStream,
+        iterator: TokenStream,
+        child_nodes: Vec<ChimeraMacroNode>,
+    },
+    Match {
+        value: TokenStream,
+        arms: Vec<(TokenStream, Vec<ChimeraMacroNode>)>,
+    },
+    Component {
+        name: Ident,
+        properties: Vec<(Ident, TokenStream)>,
+        child_nodes: Vec<ChimeraMacroNode>,
+    },
+    Splice {
+        nodes: TokenStream,
+    },
+    Fragment {
+        child_nodes: Vec<ChimeraMacroNode>,
     },
 }
 
@@ -28,6 +47,23 @@ impl ChimeraMacroNode {
                     chimera_rs::node::AnyChimeraNode::Entity(Box::new(#self))
                 }
             }
+            ChimeraMacroNode::For { .. } => {
+                quote! {
+                    chimera_rs::node::AnyChimeraNode::For(Box::new(#self))
+                }
+            }
+            ChimeraMacroNode::Match { .. } => {
+                quote! {
+                    chimera_rs::node::AnyChimeraNode::Match(Box::new(#self))
+                }
+            }
+            ChimeraMacroNode::Splice { .. } | ChimeraMacroNode::Fragment { .. } => {
+                quote! {
+                    chimera_rs::node::AnyChimeraNode::Fragment(Box::new(#self))
+                }
+            }
+            //Components already render to an AnyChimeraNode
+            ChimeraMacroNode::Component { .. } => self.to_token_stream(),
         }
     }
 }
@@ -40,11 +76,73 @@ impl ToTokens for ChimeraMacroNode {
                 ChimeraMacroNode::If {
                     condition,
                     child_nodes,
+                    else_child_nodes,
                 } => {
                     let child_nodes = child_nodes.iter().map(|c| c.to_tokens_any_node());
+                    let else_child_nodes = else_child_nodes.iter().map(|c| c.to_tokens_any_node());
+                    // The condition is evaluated up front, as the children are built alongside it
+                    // and may use the same values, such as a #for item that isn't Copy
                     tokens.extend(quote! {
                         chimera_rs::node::IfNode {
-                            condition: move || #condition,
+                            condition: {
+                                let condition: bool = #condition;
+                                move || condition
+                            },
+                            child_nodes: vec![#(#child_nodes),*],
+                            else_child_nodes: vec![#(#else_child_nodes),*],
+                        }
+                    })
+                }
+                ChimeraMacroNode::For {
+                    pattern,
+                    iterator,
+                    child_nodes,
+                } => {
+                    let child_nodes = child_nodes.iter().map(|c| c.to_tokens_any_node());
+                    tokens.extend(quote! {
+                        chimera_rs::node::ForNode {
+                            child_nodes: ::std::iter::IntoIterator::into_iter(#iterator)
+                                .map(|#pattern| vec![#(#child_nodes),*])
+                                .collect(),
+                        }
+                    })
+                }
+                ChimeraMacroNode::Match { value, arms } => {
+                    let arms = arms.iter().map(|(pattern, child_nodes)| {
+                        let child_nodes = child_nodes.iter().map(|c| c.to_tokens_any_node());
+                        quote! { #pattern => vec![#(#child_nodes),*], }
+                    });
+                    tokens.extend(quote! {
+                        chimera_rs::node::MatchNode {
+                            child_nodes: match #value { #(#arms)* },
+                        }
+                    })
+                }
+                ChimeraMacroNode::Component {
+                    name,
+                    properties,
+                    child_nodes,
+                } => {
+                    let properties = properties
+                        .iter()
+                        .map(|(property, value)| quote!(#property: #value));
+                    let child_nodes = child_nodes.iter().map(|c| c.to_tokens_any_node());
+                    tokens.extend(quote! {
+                        chimera_rs::node::ChimeraComponent::render(
+                            #name { #(#properties),* },
+                            vec![#(#child_nodes),*],
+                        )
+                    })
+                }
+                ChimeraMacroNode::Splice { nodes } => tokens.extend(quote! {
+                    chimera_rs::node::FragmentNode {
+                        child_nodes: ::std::iter::IntoIterator::into_iter(#nodes).collect(),
+                    }
+                }),
+                ChimeraMacroNode::Fragment { child_nodes } => {
+                    let child_nodes = child_nodes.iter().map(|c| c.to_tokens_any_node());
+                    tokens.extend(quote! {
+                        chimera_rs::node::FragmentNode {
                             child_nodes: vec![#(#child_nodes),*],
                         }
                     })
@@ -1,86 +1,368 @@
+use bevy::ecs::entity::Entities;
 use bevy::prelude::*;
 use bevy::tasks::futures_lite::FutureExt;
+use bevy::utils::synccell::SyncCell;
+use futures::task::AtomicWaker;
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::future::Future;
 use std::pin::Pin;
-use std::task::Waker;
-use std::task::{Context, Poll::Ready};
-use tokio::sync::mpsc::{Receiver, Sender};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::sync::oneshot;
+use tokio::time::Sleep;
 
+use crate::node::AnyChimeraNode;
+
+pub(crate) type TaskFuture =
+    Pin<Box<dyn Future<Output = Result<(), String>> + Sync + Send + 'static>>;
+pub(crate) type WorldCallbackFn = Box<dyn FnOnce(&mut World) + Send + 'static>;
+
+pub(crate) struct Task {
+    future: TaskFuture,
+    deadline: Option<Instant>,
+}
+
+/// Futures running against entities. An entity's tasks are dropped, cancelling them, once it is despawned.
+/// Tasks past their deadline are dropped too, and the entity marked as TimedOut
 #[derive(Resource)]
 pub struct AsyncTasks {
-    pub(crate) map:
-        HashMap<Entity, HashMap<usize, Pin<Box<dyn Future<Output = ()> + Sync + Send + 'static>>>>,
-    pub(crate) world_callback_tx: Sender<Box<dyn Fn(&mut World) -> () + Send + Sync>>,
+    pub(crate) map: HashMap<Entity, HashMap<usize, Task>>,
+    pub(crate) next_id: usize,
+    pub(crate) world_callback_tx: UnboundedSender<(Entity, WorldCallbackFn)>,
+    pub(crate) waker: Arc<AppWaker>,
+    pub(crate) task_timeout: Option<Duration>,
+    pub(crate) render_deadline: Option<Instant>,
+    /// Wakes the app at the earliest deadline, so tasks time out even if nothing else wakes it
+    pub(crate) deadline_timer: Option<Pin<Box<Sleep>>>,
 }
 
 impl AsyncTasks {
+    /// Set the timeout given to tasks started from now on without one of their own
+    pub fn set_task_timeout(&mut self, timeout: Option<Duration>) {
+        self.task_timeout = timeout;
+    }
+
+    /// Set a timeout for the whole render from now, after which every unfinished task times out
+    pub fn set_render_timeout(&mut self, timeout: Option<Duration>) {
+        self.render_deadline = timeout.map(|t| Instant::now() + t);
+    }
+
     pub fn run_async<F: Future<Output = ()> + Send + Sync + 'static>(
         &mut self,
         entity: Entity,
         future: impl FnOnce(WorldCallback) -> F,
+    ) {
+        self.run_async_with_timeout(entity, self.task_timeout, future);
+    }
+
+    /// Run a future against an entity, timing it out if it hasn't finished within timeout
+    pub fn run_async_with_timeout<F: Future<Output = ()> + Send + Sync + 'static>(
+        &mut self,
+        entity: Entity,
+        timeout: Option<Duration>,
+        future: impl FnOnce(WorldCallback) -> F,
+    ) {
+        self.try_run_async_with_timeout(entity, timeout, |world_cb| {
+            let future = future(world_cb);
+            async move {
+                future.await;
+                Ok::<(), String>(())
+            }
+        });
+    }
+
+    /// Run a fallible future against an entity. If it fails, the entity's other tasks are dropped
+    /// and the error is added to it as an AsyncError, to be rendered by the nearest ErrorBoundary
+    pub fn try_run_async<E: Display, F: Future<Output = Result<(), E>> + Send + Sync + 'static>(
+        &mut self,
+        entity: Entity,
+        future: impl FnOnce(WorldCallback) -> F,
+    ) {
+        self.try_run_async_with_timeout(entity, self.task_timeout, future);
+    }
+
+    /// Run a fallible future against an entity, timing it out if it hasn't finished within timeout
+    pub fn try_run_async_with_timeout<
+        E: Display,
+        F: Future<Output = Result<(), E>> + Send + Sync + 'static,
+    >(
+        &mut self,
+        entity: Entity,
+        timeout: Option<Duration>,
+        future: impl FnOnce(WorldCallback) -> F,
     ) {
         let world_cb = WorldCallback {
+            entity,
             world_tx: self.world_callback_tx.clone(),
+            waker: self.waker.clone(),
+        };
+        let future = future(world_cb);
+        let id = self.next_id;
+        self.next_id += 1;
+        let task = Task {
+            future: Box::pin(async move { future.await.map_err(|e| e.to_string()) }),
+            deadline: timeout.map(|t| Instant::now() + t),
         };
-        let hm = self.map.entry(entity).or_insert_with(HashMap::new);
-        hm.insert(hm.len(), Box::pin(future(world_cb)));
+        self.map.entry(entity).or_default().insert(id, task);
+        // Tasks started after this update's tasks were polled are only polled on the next one
+        self.waker.wake_by_ref();
     }
 }
 
+/// The error a task running against this entity failed with
+#[derive(Component, Debug, Clone)]
+pub struct AsyncError(pub String);
+
+/// Replaces this entity's children with the subtree it returns, when a task running against
+/// the entity or one of its descendants fails
+#[derive(Component, Clone)]
+pub struct ErrorBoundary(pub fn(&AsyncError) -> AnyChimeraNode);
+
+/// Marks an entity whose tasks were dropped for running past their deadline
+#[derive(Component, Debug, Clone)]
+pub struct TimedOut;
+
+/// Replaces this entity's children with the subtree it returns, when a task running against
+/// the entity or one of its descendants times out
+#[derive(Component, Clone)]
+pub struct TimeoutBoundary(pub fn() -> AnyChimeraNode);
+
 pub struct WorldCallback {
-    world_tx: Sender<Box<dyn Fn(&mut World) -> () + Send + Sync + 'static>>,
+    entity: Entity,
+    world_tx: UnboundedSender<(Entity, WorldCallbackFn)>,
+    waker: Arc<AppWaker>,
 }
 
 impl WorldCallback {
-    pub async fn with_world(&self, cb: impl Fn(&mut World) -> () + Send + Sync + 'static) {
-        self.world_tx.send(Box::new(cb)).await.unwrap();
-    }
-}
-
-/// Poll all async tasks stored against entities, and remove them if they are polled to completion
-pub(crate) fn update_tasks(mut async_tasks: ResMut<AsyncTasks>, waker: Res<AsyncWaker>) {
-    match &waker.0 {
-        None => {}
-        Some(waker) => {
-            let mut context = Context::from_waker(&waker);
-            let mut completed_entities = Vec::<Entity>::new();
-
-            for (entity, hm) in async_tasks.map.iter_mut() {
-                let mut completed_ids = Vec::<usize>::new();
-                for (id, action) in hm.iter_mut() {
-                    if let Ready(_) = action.poll(&mut context) {
-                        completed_entities.push(*entity);
-                        completed_ids.push(*id);
-                    }
+    /// The entity the task is running against
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    /// Run a callback against the world. It is queued once awaited, and skipped if the task's
+    /// entity has been despawned by the time it would run, or if the app has been dropped
+    pub fn with_world(
+        &self,
+        cb: impl FnOnce(&mut World) + Send + 'static,
+    ) -> impl Future<Output = ()> + Send + Sync + '_ {
+        // The callback only needs to be Send, but task futures are Sync
+        let cb = SyncCell::new(cb);
+        async move {
+            self.send(Box::new(SyncCell::to_inner(cb)));
+        }
+    }
+
+    /// Run a callback against the world, resolving to what it returns once the app has run it.
+    /// Like with_world, it is queued once awaited.
+    /// If the callback is skipped the future never resolves, as the task is about to be dropped
+    pub fn with_world_returning<R: Send + 'static>(
+        &self,
+        cb: impl FnOnce(&mut World) -> R + Send + 'static,
+    ) -> impl Future<Output = R> + Send + Sync + '_ {
+        let cb = SyncCell::new(cb);
+        async move {
+            let cb = SyncCell::to_inner(cb);
+            let (tx, rx) = oneshot::channel();
+            self.send(Box::new(move |world| {
+                let _ = tx.send(cb(world));
+            }));
+            if let Ok(value) = rx.await {
+                return value;
+            }
+            std::future::pending().await
+        }
+    }
+
+    fn send(&self, cb: WorldCallbackFn) {
+        if self.world_tx.send((self.entity, cb)).is_ok() {
+            // The callback only runs when the app next updates
+            self.waker.wake_by_ref();
+        }
+    }
+}
+
+/// Wakes whatever is driving the app when a task or world callback needs the schedule run again.
+/// Task futures are polled with this as their waker
+#[derive(Default)]
+pub struct AppWaker {
+    woken: AtomicBool,
+    waker: AtomicWaker,
+}
+
+impl AppWaker {
+    /// Register the waker of whatever drives the app, returning whether the app has been woken
+    /// since it was last registered
+    pub(crate) fn register(&self, waker: &Waker) -> bool {
+        self.waker.register(waker);
+        self.woken.swap(false, Ordering::AcqRel)
+    }
+
+    fn wake_by_ref(&self) {
+        self.woken.store(true, Ordering::Release);
+        self.waker.wake();
+    }
+}
+
+impl Wake for AppWaker {
+    fn wake(self: Arc<Self>) {
+        AppWaker::wake_by_ref(&self);
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        AppWaker::wake_by_ref(self);
+    }
+}
+
+/// Poll all async tasks stored against entities, and remove them if they are polled to completion.
+/// Tasks of entities which have been despawned are dropped first, as are those of entities
+/// with a failed or timed out task
+pub(crate) fn update_tasks(
+    mut commands: Commands,
+    mut async_tasks: ResMut<AsyncTasks>,
+    entities: &Entities,
+) {
+    async_tasks
+        .map
+        .retain(|entity, _| entities.contains(*entity));
+    let waker = Waker::from(async_tasks.waker.clone());
+    let mut context = Context::from_waker(&waker);
+    let now = Instant::now();
+    let render_deadline = async_tasks.render_deadline;
+    let mut next_deadline = None::<Instant>;
+    let mut completed_entities = Vec::<Entity>::new();
+    let mut stopped_entities = Vec::<Entity>::new();
+
+    for (entity, hm) in async_tasks.map.iter_mut() {
+        let mut completed_ids = Vec::<usize>::new();
+        for (id, task) in hm.iter_mut() {
+            let deadline = earliest(task.deadline, render_deadline);
+            if deadline.is_some_and(|d| d <= now) {
+                commands.entity(*entity).insert(TimedOut);
+                stopped_entities.push(*entity);
+                break;
+            }
+            match task.future.poll(&mut context) {
+                Poll::Ready(Ok(())) => {
+                    completed_entities.push(*entity);
+                    completed_ids.push(*id);
                 }
-                for id in completed_ids {
-                    hm.remove(&id);
+                Poll::Ready(Err(error)) => {
+                    commands.entity(*entity).insert(AsyncError(error));
+                    stopped_entities.push(*entity);
+                    break;
                 }
-            }
-            for entity in completed_entities {
-                match async_tasks.map.get(&entity) {
-                    Some(hm) if hm.len() == 0 => {
-                        async_tasks.map.remove(&entity);
-                    }
-                    _ => {}
+                Poll::Pending => {
+                    next_deadline = earliest(next_deadline, deadline);
                 }
             }
         }
+        for id in completed_ids {
+            hm.remove(&id);
+        }
+    }
+    for entity in completed_entities {
+        match async_tasks.map.get(&entity) {
+            Some(hm) if hm.is_empty() => {
+                async_tasks.map.remove(&entity);
+            }
+            _ => {}
+        }
+    }
+    for entity in stopped_entities {
+        async_tasks.map.remove(&entity);
+    }
+
+    // The timer needs a tokio runtime. Without one, deadlines are only checked when something else
+    // wakes the app
+    match next_deadline.filter(|_| Handle::try_current().is_ok()) {
+        Some(deadline) => {
+            let deadline = tokio::time::Instant::from_std(deadline);
+            let timer = async_tasks
+                .deadline_timer
+                .get_or_insert_with(|| Box::pin(tokio::time::sleep_until(deadline)));
+            timer.as_mut().reset(deadline);
+            let _ = timer.as_mut().poll(&mut context);
+        }
+        None => async_tasks.deadline_timer = None,
+    }
+}
+
+fn earliest(a: Option<Instant>, b: Option<Instant>) -> Option<Instant> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// The nearest boundary of type B to an entity, starting with the entity itself
+fn nearest_boundary<'a, B: Component>(
+    entity: Entity,
+    parents: &Query<&Parent>,
+    boundaries: &'a Query<&B>,
+) -> Option<(Entity, &'a B)> {
+    std::iter::once(entity)
+        .chain(parents.iter_ancestors(entity))
+        .find_map(|e| boundaries.get(e).ok().map(|b| (e, b)))
+}
+
+fn replace_children(commands: &mut Commands, entity: Entity, node: AnyChimeraNode) {
+    commands
+        .entity(entity)
+        .despawn_descendants()
+        .with_children(|builder| {
+            node.spawn_with_child_builder(builder);
+        });
+}
+
+/// Swap the children of the nearest error boundary for its error subtree, when a task fails.
+/// Errors with no boundary to render them are logged instead
+pub(crate) fn apply_error_boundaries(
+    mut commands: Commands,
+    errors: Query<(Entity, &AsyncError), Added<AsyncError>>,
+    parents: Query<&Parent>,
+    boundaries: Query<&ErrorBoundary>,
+) {
+    for (entity, error) in errors.iter() {
+        match nearest_boundary(entity, &parents, &boundaries) {
+            Some((boundary, ErrorBoundary(render))) => {
+                replace_children(&mut commands, boundary, render(error));
+            }
+            None => error!("Async task on {:?} failed: {}", entity, error.0),
+        }
+    }
+}
+
+/// Swap the children of the nearest timeout boundary for its fallback subtree, when a task times out
+pub(crate) fn apply_timeout_boundaries(
+    mut commands: Commands,
+    timed_out: Query<Entity, Added<TimedOut>>,
+    parents: Query<&Parent>,
+    boundaries: Query<&TimeoutBoundary>,
+) {
+    for entity in timed_out.iter() {
+        if let Some((boundary, TimeoutBoundary(render))) =
+            nearest_boundary(entity, &parents, &boundaries)
+        {
+            replace_children(&mut commands, boundary, render());
+        }
     }
 }
 
 #[derive(Resource)]
 pub(crate) struct AsyncRx {
-    pub(crate) world_callback_rx: Receiver<Box<dyn Fn(&mut World) -> () + Send + Sync + 'static>>,
+    pub(crate) world_callback_rx: UnboundedReceiver<(Entity, WorldCallbackFn)>,
 }
 
-/// Run async with_world and with_commands callbacks
+/// Run async with_world and with_commands callbacks, skipping those from despawned entities
 pub(crate) fn process_world_callbacks(world: &mut World) {
-    while let Ok(cb) = world.resource_mut::<AsyncRx>().world_callback_rx.try_recv() {
-        cb(world);
+    while let Ok((entity, cb)) = world.resource_mut::<AsyncRx>().world_callback_rx.try_recv() {
+        if world.get_entity(entity).is_some() {
+            cb(world);
+        }
     }
 }
-
-#[derive(Resource)]
-pub struct AsyncWaker(pub Option<Waker>);
@@ -1,4 +1,9 @@
-use crate::html::{plugin::RenderHtmlPlugin, stream::AppHtmlStream};
+use std::time::Duration;
+
+use crate::{
+    html::{document::RenderMode, plugin::RenderHtmlPlugin, stream::AppHtmlStream},
+    r#async::AsyncTasks,
+};
 use axum_core::{
     body::Body,
     response::{IntoResponse, Response},
@@ -7,6 +12,7 @@ use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
 
 pub struct AxumHtmlApp {
     pub app: App,
+    render_timeout: Option<Duration>,
 }
 
 impl AxumHtmlApp {
@@ -14,7 +20,17 @@ impl AxumHtmlApp {
         let mut app = App::new();
         app.add_plugins(RenderHtmlPlugin);
         app.add_systems(Startup, init);
-        Self { app }
+        Self {
+            app,
+            render_timeout: None,
+        }
+    }
+
+    /// Render a whole document, with a doctype and html, head and body elements
+    pub fn document<S>(init: impl IntoSystemConfigs<S>) -> Self {
+        let mut html_app = Self::new(init);
+        html_app.app.insert_resource(RenderMode::Document);
+        html_app
     }
 
     pub fn add_systems<S>(
@@ -25,10 +41,23 @@ impl AxumHtmlApp {
         self.app.add_systems(schedule, systems);
         self
     }
+
+    /// Time out every task still running this long after the response starts streaming,
+    /// swapping in timeout boundaries and finishing the response
+    pub fn set_render_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.render_timeout = timeout;
+        self
+    }
 }
 
 impl IntoResponse for AxumHtmlApp {
-    fn into_response(self) -> Response {
+    fn into_response(mut self) -> Response {
+        if let Some(timeout) = self.render_timeout {
+            self.app
+                .world
+                .resource_mut::<AsyncTasks>()
+                .set_render_timeout(Some(timeout));
+        }
         Body::from_stream(AppHtmlStream::new(self.app)).into_response()
     }
 }
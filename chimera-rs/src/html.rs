@@ -1,11 +1,21 @@
 pub mod attributes;
+pub mod classes;
+pub mod css;
+pub mod document;
+pub mod escape;
 pub mod plugin;
 pub mod render;
+pub mod sink;
 pub mod stream;
 pub mod styles;
+pub mod suspense;
 pub mod tag;
 pub mod text;
 
+pub use classes::Classes;
+pub use document::{InHead, RenderMode};
+pub use sink::{render_app_to_string, render_to_fmt, render_to_io, render_to_string, RenderError};
 pub use styles::*;
+pub use suspense::Suspense;
 pub use tag::*;
 pub use text::*;
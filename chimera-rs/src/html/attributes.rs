@@ -1,23 +1,27 @@
-use std::collections::HashMap;
-
 use bevy::prelude::*;
+use indexmap::IndexMap;
 
 use super::tag::Tag;
 
+/// Attribute names to values, rendered in insertion order
+pub type AttributeMap = IndexMap<String, String>;
+
 /// Attributes that can actually be set when you're feeling lazy
-#[derive(Component, Debug)]
-pub struct Attributes(pub HashMap<String, String>);
+#[derive(Component, Debug, Clone)]
+pub struct Attributes(pub AttributeMap);
 
-///Attributes that are rendered when the page is built
+///Attributes that are rendered when the page is built.
+///Attributes come first in the order they were set, then class and style are merged in place,
+///or appended in that order if they weren't set as attributes
 #[derive(Component, Debug)]
-pub struct RenderAttributes(pub HashMap<String, String>);
+pub struct RenderAttributes(pub AttributeMap);
 
 /// Every update stage render attributes are reset to nothing
 pub fn reset_render_attributes(mut commands: Commands, query: Query<Entity, With<Tag>>) {
     for entity in &query {
         commands
             .entity(entity)
-            .insert(RenderAttributes(HashMap::new()));
+            .insert(RenderAttributes(AttributeMap::new()));
     }
 }
 
@@ -0,0 +1,76 @@
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+
+use super::attributes::RenderAttributes;
+
+/// Classes for an entity, each with whether it is applied.
+/// Merged with any class attribute, and every class is only rendered once
+#[derive(Component, Debug, Clone, Default)]
+pub struct Classes(pub Vec<(Cow<'static, str>, bool)>);
+
+impl Classes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(self, class: impl Into<Cow<'static, str>>) -> Self {
+        self.with_if(class, true)
+    }
+
+    /// Add a class which is only applied if condition is true
+    pub fn with_if(mut self, class: impl Into<Cow<'static, str>>, condition: bool) -> Self {
+        self.0.push((class.into(), condition));
+        self
+    }
+
+    /// Set whether a class is applied, adding it if it isn't there
+    pub fn set(&mut self, class: impl Into<Cow<'static, str>>, condition: bool) {
+        let class = class.into();
+        match self.0.iter_mut().find(|(c, _)| *c == class) {
+            Some(entry) => entry.1 = condition,
+            None => self.0.push((class, condition)),
+        }
+    }
+
+    pub fn contains(&self, class: &str) -> bool {
+        self.0.iter().any(|(c, applied)| *applied && c == class)
+    }
+
+    /// The applied classes, in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0
+            .iter()
+            .filter(|(_, applied)| *applied)
+            .map(|(c, _)| c.as_ref())
+    }
+}
+
+impl<C: Into<Cow<'static, str>>> FromIterator<C> for Classes {
+    fn from_iter<T: IntoIterator<Item = C>>(iter: T) -> Self {
+        Classes(iter.into_iter().map(|c| (c.into(), true)).collect())
+    }
+}
+
+/// Merge classes into the class attribute, after any set through attributes
+pub fn apply_classes(mut commands: Commands, query: Query<(Entity, &RenderAttributes, &Classes)>) {
+    for (entity, render_attributes, classes) in &query {
+        let mut new_attributes = render_attributes.0.clone();
+        let existing = new_attributes.get("class").cloned().unwrap_or_default();
+        let mut merged: Vec<&str> = vec![];
+        for c in existing.split_whitespace().chain(classes.iter()) {
+            if !merged.contains(&c) {
+                merged.push(c);
+            }
+        }
+        // Inserting keeps the position of a class attribute, so attribute order is stable
+        if merged.is_empty() {
+            new_attributes.shift_remove("class");
+        } else {
+            new_attributes.insert("class".to_string(), merged.join(" "));
+        }
+        commands
+            .entity(entity)
+            .insert(RenderAttributes(new_attributes));
+    }
+}
@@ -0,0 +1,153 @@
+use std::borrow::Cow;
+use std::fmt;
+
+macro_rules! css_keywords {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident => $keyword:literal),+ $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant),+
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(match self {
+                    $($name::$variant => $keyword),+
+                })
+            }
+        }
+    };
+}
+
+/// A css length, such as 10px or 50%
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Auto,
+    Px(f32),
+    Em(f32),
+    Rem(f32),
+    Percent(f32),
+    Vw(f32),
+    Vh(f32),
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Length::Auto => f.write_str("auto"),
+            Length::Px(v) => write!(f, "{}px", v),
+            Length::Em(v) => write!(f, "{}em", v),
+            Length::Rem(v) => write!(f, "{}rem", v),
+            Length::Percent(v) => write!(f, "{}%", v),
+            Length::Vw(v) => write!(f, "{}vw", v),
+            Length::Vh(v) => write!(f, "{}vh", v),
+        }
+    }
+}
+
+/// Bare numbers are pixels
+impl From<f32> for Length {
+    fn from(px: f32) -> Self {
+        Length::Px(px)
+    }
+}
+
+/// A css color, either a named color like red or an rgb value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Color {
+    Named(Cow<'static, str>),
+    Hex(u32),
+    Rgb(u8, u8, u8),
+    Rgba(u8, u8, u8, f32),
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Named(name) => f.write_str(name),
+            Color::Hex(hex) => write!(f, "#{:06x}", hex),
+            Color::Rgb(r, g, b) => write!(f, "rgb({},{},{})", r, g, b),
+            Color::Rgba(r, g, b, a) => write!(f, "rgba({},{},{},{})", r, g, b, a),
+        }
+    }
+}
+
+impl From<&'static str> for Color {
+    fn from(name: &'static str) -> Self {
+        Color::Named(name.into())
+    }
+}
+
+impl From<String> for Color {
+    fn from(name: String) -> Self {
+        Color::Named(name.into())
+    }
+}
+
+css_keywords!(Display {
+    None => "none",
+    Block => "block",
+    Inline => "inline",
+    InlineBlock => "inline-block",
+    Flex => "flex",
+    InlineFlex => "inline-flex",
+    Grid => "grid",
+    InlineGrid => "inline-grid",
+    Contents => "contents",
+});
+
+css_keywords!(Position {
+    Static => "static",
+    Relative => "relative",
+    Absolute => "absolute",
+    Fixed => "fixed",
+    Sticky => "sticky",
+});
+
+css_keywords!(FlexDirection {
+    Row => "row",
+    RowReverse => "row-reverse",
+    Column => "column",
+    ColumnReverse => "column-reverse",
+});
+
+css_keywords!(FlexWrap {
+    NoWrap => "nowrap",
+    Wrap => "wrap",
+    WrapReverse => "wrap-reverse",
+});
+
+css_keywords!(JustifyContent {
+    Start => "start",
+    End => "end",
+    FlexStart => "flex-start",
+    FlexEnd => "flex-end",
+    Center => "center",
+    SpaceBetween => "space-between",
+    SpaceAround => "space-around",
+    SpaceEvenly => "space-evenly",
+});
+
+css_keywords!(AlignItems {
+    Start => "start",
+    End => "end",
+    FlexStart => "flex-start",
+    FlexEnd => "flex-end",
+    Center => "center",
+    Baseline => "baseline",
+    Stretch => "stretch",
+});
+
+css_keywords!(TextAlign {
+    Left => "left",
+    Right => "right",
+    Center => "center",
+    Justify => "justify",
+});
+
+css_keywords!(Overflow {
+    Visible => "visible",
+    Hidden => "hidden",
+    Scroll => "scroll",
+    Auto => "auto",
+});
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use super::{
+    render::RenderTag,
+    tag::{Base, Body, Head, Html, Link, Math, Meta, Svg, Tag, Title},
+    text::{RawHtml, Text},
+};
+
+/// Whether the page is rendered as is, or as a whole document.
+/// Documents start with a doctype and always have html, head and body elements.
+/// Anything spawned outside of them is moved into body, and head content into head
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    #[default]
+    Fragment,
+    Document,
+}
+
+/// Hoists an element into head in document mode, alongside title, meta, link and base elements.
+/// Those within svg or math are left in place, as they are part of the drawing or formula there
+#[derive(Component, Debug, Clone, Copy)]
+pub struct InHead;
+
+type HeadContent = Or<(
+    With<Title>,
+    With<Meta>,
+    With<Link>,
+    With<Base>,
+    With<InHead>,
+)>;
+
+/// Find an element with the given tag among entities, or spawn one
+fn find_or_spawn(
+    world: &mut World,
+    entities: &[Entity],
+    tag: &'static str,
+    bundle: impl Bundle,
+) -> Entity {
+    entities
+        .iter()
+        .copied()
+        .find(|e| world.get::<Tag>(*e).is_some_and(|t| t.0 == tag))
+        .unwrap_or_else(|| world.spawn((bundle, Tag(tag))).id())
+}
+
+/// Whether an entity is within svg or math, where title and link elements aren't head content
+fn in_foreign_content(world: &World, entity: Entity) -> bool {
+    std::iter::successors(world.get::<Parent>(entity), |p| {
+        world.get::<Parent>(p.get())
+    })
+    .any(|p| world.get::<Svg>(p.get()).is_some() || world.get::<Math>(p.get()).is_some())
+}
+
+fn children_of(world: &World, entity: Entity) -> Vec<Entity> {
+    world
+        .get::<Children>(entity)
+        .map(|c| c.to_vec())
+        .unwrap_or_default()
+}
+
+/// Make sure html, head and body exist, and move root and head content into them.
+/// Runs once tags are applied, so elements spawned this update are placed before they render
+pub(crate) fn scaffold_document(world: &mut World) {
+    let mut roots = world
+        .query_filtered::<Entity, (Or<(With<Tag>, With<Text>, With<RawHtml>)>, Without<Parent>)>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    // Roots go into body in entity id order, the same order write_output renders them in
+    roots.sort();
+
+    let html = find_or_spawn(world, &roots, "html", Html);
+    let candidates = [children_of(world, html), roots.clone()].concat();
+    let head = find_or_spawn(world, &candidates, "head", Head);
+    let body = find_or_spawn(world, &candidates, "body", Body);
+    if world.get::<Parent>(head).map(|p| p.get()) != Some(html) {
+        world.entity_mut(html).insert_children(0, &[head]);
+    }
+    if world.get::<Parent>(body).map(|p| p.get()) != Some(html) {
+        world.entity_mut(html).add_child(body);
+    }
+
+    // Once head has been sent, later head content stays where it is
+    let head_open = !matches!(
+        world.get::<RenderTag>(head),
+        Some(RenderTag::Consumed | RenderTag::OpenConsumed { .. })
+    );
+    let head_content = if head_open {
+        world
+            .query_filtered::<Entity, HeadContent>()
+            .iter(world)
+            .filter(|e| world.get::<InHead>(*e).is_some() || !in_foreign_content(world, *e))
+            .collect::<Vec<_>>()
+    } else {
+        vec![]
+    };
+    for root in roots {
+        if root != html && root != head && root != body && !head_content.contains(&root) {
+            world.entity_mut(body).add_child(root);
+        }
+    }
+
+    // Anything already streamed stays where it is
+    let mut hoisted = head_content
+        .into_iter()
+        .filter(|e| world.get::<Parent>(*e).map(|p| p.get()) != Some(head))
+        .filter(|e| world.get::<RenderTag>(*e).is_none())
+        .collect::<Vec<_>>();
+    hoisted.sort();
+    world.entity_mut(head).push_children(&hoisted);
+}
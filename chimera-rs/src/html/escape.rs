@@ -0,0 +1,38 @@
+/// Escape text content so it is never interpreted as markup
+pub fn escape_text(text: &str) -> String {
+    escape(text, false)
+}
+
+/// Escape an attribute value so it can be safely placed inside double quotes
+pub fn escape_attribute(value: &str) -> String {
+    escape(value, true)
+}
+
+fn escape(input: &str, quotes: bool) -> String {
+    let mut output = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' if quotes => output.push_str("&quot;"),
+            '\'' if quotes => output.push_str("&#39;"),
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+/// Escape a css property name, value or media query so it can't end its declaration,
+/// rule or style block. The characters are replaced with css escapes, leaving the value invalid
+/// rather than letting it add rules or markup
+pub fn escape_css(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '<' | '{' | '}' | ';' => output.push_str(&format!("\\{:x} ", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output
+}
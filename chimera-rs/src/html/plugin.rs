@@ -1,16 +1,25 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 use tokio::sync::mpsc;
 
 use crate::{
     html::{
         attributes::{add_attributes_to_render_attributes, reset_render_attributes},
-        render::{add_render_tags, add_render_tags_for_text, render_tags_to_output, RenderOutput},
-        styles::apply_styles,
+        classes::apply_classes,
+        document::{scaffold_document, RenderMode},
+        render::{
+            add_render_tags, add_render_tags_for_raw_html, add_render_tags_for_text,
+            render_tags_to_output, RenderOutput,
+        },
+        sink::ManualRender,
+        styles::{apply_styles, StyleMode, Stylesheet},
+    },
+    r#async::{
+        apply_error_boundaries, apply_timeout_boundaries, process_world_callbacks, update_tasks,
+        AppWaker, AsyncRx, AsyncTasks,
     },
-    r#async::{process_world_callbacks, update_tasks, AsyncRx, AsyncTasks},
 };
 
-use super::tag::{Main, Time, *};
+use super::tag::tag_systems;
 use bevy::prelude::*;
 use either::Either;
 
@@ -22,80 +31,90 @@ enum HtmlRenderSet {
     RenderTags,
 }
 
-macro_rules! add_tag_systems {
-    ($app:ident, ($($tag:ident),+), $($group:tt),+) => {
-      add_tag_systems!($app, ($($tag),*));
-      add_tag_systems!($app, $($group),*);
-    };
-    ($app:ident, ($($tag:ident),+)) => {
-      $app.add_systems(Update, ($(make_tag::<$tag>),*).in_set(HtmlRenderSet::ApplyTags));
-    };
-}
-
 pub struct RenderHtmlPlugin;
 impl Plugin for RenderHtmlPlugin {
     fn build(&self, app: &mut App) {
-        add_tag_systems!(
-            app,
-            (Div, H1, H2, H3, H4, H5, H6, P, Span, A),
-            (Img, Button, Input, Label, Select, Option, Textarea, Form, Table, Tr),
-            (Td, Th, Thead, Tbody, Tfoot, Ul, Ol, Li, Dl, Dt),
-            (Dd, Section, Header, Footer, Main, Article, Aside, Nav, Address, Blockquote),
-            (Details, Summary, Dialog, Menu, MenuItem, Figure, Figcaption, Audio, Video),
-            (
-                Canvas, Embed, Object, Source, Track, Map, Area, Math, Svg, Iframe, Frame,
-                Frameset, Noframes
-            ),
-            (
-                B, Strong, I, Em, Mark, Small, Del, Ins, Sub, Sup, Pre, Code, Var, Samp, Kbd, Q,
-                Cite, Abbr
-            ),
-            (Dfn, Time, Progress, Meter, Br, Wbr, Template, Slot, Script, Noscript, Style, Meta),
-            (Link, Title, Base, Head, Html, Body)
+        app.add_systems(Update, tag_systems().in_set(HtmlRenderSet::ApplyTags));
+        // Place everything in html, head and body when rendering a whole document
+        app.add_systems(
+            Update,
+            scaffold_document
+                .run_if(resource_equals(RenderMode::Document))
+                .after(HtmlRenderSet::ApplyTags),
         );
+
         // Reset our render attribute components
         app.add_systems(
             Update,
-            reset_render_attributes.after(HtmlRenderSet::ApplyTags),
+            reset_render_attributes
+                .after(HtmlRenderSet::ApplyTags)
+                .after(scaffold_document),
         );
 
-        app.add_systems(Update, (update_tasks, process_world_callbacks));
-
-        // Apply attributes to render attributes
+        // Callbacks sent by tasks as they finish are run the same frame,
+        // before tags are applied so anything they spawn is rendered straight away.
+        // Error and timeout boundaries go last so their subtree replaces whatever failed tasks spawned
         app.add_systems(
-            PostUpdate,
-            (add_attributes_to_render_attributes).in_set(HtmlRenderSet::ApplyAttributes),
+            Update,
+            (
+                update_tasks,
+                process_world_callbacks,
+                apply_error_boundaries,
+                apply_timeout_boundaries,
+            )
+                .chain()
+                .before(HtmlRenderSet::ApplyTags),
         );
 
-        // Apply styles to render attributes
+        // Apply attributes, then classes, then styles to render attributes.
+        // Chained as each rebuilds RenderAttributes from the previous one's output
         app.add_systems(
             PostUpdate,
-            (apply_styles).in_set(HtmlRenderSet::ApplyAttributes),
+            (
+                add_attributes_to_render_attributes,
+                apply_classes,
+                apply_styles,
+            )
+                .chain()
+                .in_set(HtmlRenderSet::ApplyAttributes),
         );
 
         // Render out our tags to render tags
         app.add_systems(
             PostUpdate,
-            (add_render_tags, add_render_tags_for_text)
+            (
+                add_render_tags,
+                add_render_tags_for_text,
+                add_render_tags_for_raw_html,
+            )
                 .in_set(HtmlRenderSet::AddTags)
                 .after(HtmlRenderSet::ApplyAttributes),
         );
 
-        // Walk render tags from page down and write to output
+        // Walk render tags from page down and write to output, unless they're written by hand
         app.add_systems(
             PostUpdate,
             render_tags_to_output
+                .run_if(not(resource_exists::<ManualRender>))
                 .in_set(HtmlRenderSet::RenderTags)
                 .after(HtmlRenderSet::AddTags)
                 .after(HtmlRenderSet::ApplyAttributes),
         );
 
-        let (world_callback_tx, world_callback_rx) = mpsc::channel(100);
+        let (world_callback_tx, world_callback_rx) = mpsc::unbounded_channel();
         app.insert_resource(AsyncTasks {
             map: HashMap::new(),
+            next_id: 0,
             world_callback_tx,
+            waker: Arc::new(AppWaker::default()),
+            task_timeout: None,
+            render_deadline: None,
+            deadline_timer: None,
         });
         app.insert_resource(AsyncRx { world_callback_rx });
         app.insert_resource(RenderOutput(Either::Left(String::new())));
+        app.init_resource::<StyleMode>();
+        app.init_resource::<RenderMode>();
+        app.init_resource::<Stylesheet>();
     }
 }
@@ -2,14 +2,24 @@ use either::Either;
 
 use crate::r#async::AsyncTasks;
 
-use super::{attributes::RenderAttributes, tag::Tag, text::Text};
+use super::{
+    attributes::RenderAttributes,
+    document::RenderMode,
+    escape::{escape_attribute, escape_text},
+    styles::Stylesheet,
+    suspense::{
+        is_waiting, render_placeholder, replacement_close, replacement_open, Suspended, Suspense,
+    },
+    tag::{Head, Html, Tag},
+    text::{RawHtml, Text},
+};
 use bevy::prelude::*;
 use std::fmt::Write;
 
 #[derive(Component, Clone, Debug)]
 pub(crate) enum RenderTag {
     Waiting,
-    SelfClosing(String),
+    Void(String),
     OpenClose { open: String, close: String },
     Consumed,
     OpenConsumed { close: String },
@@ -19,33 +29,76 @@ pub(crate) enum RenderTag {
 #[derive(Resource)]
 pub(crate) struct RenderOutput(pub(crate) Either<String, String>);
 
-static NO_SELF_CLOSE_TAGS: [&str; 3] = ["script", "style", "template"];
+/// Elements which can't have content, so are rendered without a closing tag
+static VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Attributes which are true by being present, and left out entirely when set to "false"
+static BOOLEAN_ATTRIBUTES: [&str; 24] = [
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "hidden",
+    "inert",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "playsinline",
+    "readonly",
+    "required",
+    "selected",
+];
+
+fn render_attribute(name: &str, value: &str) -> String {
+    if BOOLEAN_ATTRIBUTES.contains(&name) {
+        match value {
+            "false" => String::new(),
+            _ => format!(" {}", name),
+        }
+    } else {
+        format!(" {}=\"{}\"", name, escape_attribute(value))
+    }
+}
 
 ///Render a node instance, will be partial if component templates don't exist yet
 pub(crate) fn get_render_tag(
     entity: Entity,
     tag: &Tag,
     attributes: &RenderAttributes,
-    children: Option<&Children>,
+    raw_html: Option<&RawHtml>,
     async_tasks: &Res<AsyncTasks>,
 ) -> Result<RenderTag, std::fmt::Error> {
-    if async_tasks.map.get(&entity).is_some_and(|s| s.len() > 0) {
+    if async_tasks.map.get(&entity).is_some_and(|s| !s.is_empty()) {
         return Ok(RenderTag::Waiting);
     }
     let mut open = String::new();
     let attributes_string = attributes
         .0
         .iter()
-        .map(|(k, v)| format!(" {}=\"{}\"", k, v))
-        .collect::<Vec<String>>()
-        .join(" ");
-    write!(open, "<{}{}", tag.0, attributes_string)?;
-    // Some tags don't like self_closing
-    if !children.is_some_and(|c| c.len() > 0) && !NO_SELF_CLOSE_TAGS.contains(&tag.0) {
-        write!(open, " />")?;
-        return Ok(RenderTag::SelfClosing(open));
-    } else {
-        write!(open, ">")?;
+        .map(|(k, v)| render_attribute(k, v))
+        .collect::<String>();
+    write!(open, "<{}{}>", tag.0, attributes_string)?;
+    // Void elements have no closing tag, so any content they were given is left out
+    if VOID_ELEMENTS.contains(&tag.0) {
+        return Ok(RenderTag::Void(open));
+    }
+    // Raw html on a tagged entity is its trusted inner markup, placed before any children
+    if let Some(raw_html) = raw_html {
+        open.push_str(&raw_html.0);
     }
     Ok(RenderTag::OpenClose {
         open,
@@ -53,24 +106,25 @@ pub(crate) fn get_render_tag(
     })
 }
 
+type RenderTagQuery<'a> = (
+    Entity,
+    &'a Tag,
+    &'a RenderAttributes,
+    Option<&'a RawHtml>,
+    Option<&'a RenderTag>,
+);
+
 //System to actually add the tags
 pub(crate) fn add_render_tags(
     mut commands: Commands,
-    query: Query<(
-        Entity,
-        &Tag,
-        &RenderAttributes,
-        Option<&RenderTag>,
-        Option<&Children>,
-    )>,
+    query: Query<RenderTagQuery>,
     async_tasks: Res<AsyncTasks>,
 ) {
-    for (entity, tag, attributes, render_tag, children) in &query {
+    for (entity, tag, attributes, raw_html, render_tag) in &query {
         match render_tag {
             None | Some(RenderTag::Waiting) => {
-                dbg!("Adding render tag to entity", entity);
                 commands.entity(entity).insert(
-                    get_render_tag(entity, tag, attributes, children, &async_tasks)
+                    get_render_tag(entity, tag, attributes, raw_html, &async_tasks)
                         .expect("Error rendering tag"),
                 );
             }
@@ -90,97 +144,208 @@ pub(crate) fn add_render_tags_for_text(
             None | Some(RenderTag::Waiting) => {
                 commands
                     .entity(entity)
-                    .insert(RenderTag::Text(text.0.to_string()));
+                    .insert(RenderTag::Text(escape_text(&text.0)));
             }
             _ => continue,
         }
     }
 }
 
+//System to add render tags to untagged raw html components, which are output without escaping
+pub(crate) fn add_render_tags_for_raw_html(
+    mut commands: Commands,
+    query: Query<(Entity, &RawHtml, Option<&RenderTag>), Without<Tag>>,
+) {
+    for (entity, raw_html, render_tag) in &query {
+        match render_tag {
+            None | Some(RenderTag::Waiting) => {
+                commands
+                    .entity(entity)
+                    .insert(RenderTag::Text(raw_html.0.to_string()));
+            }
+            _ => continue,
+        }
+    }
+}
+
+/// How far writing got, stopping at the first entity still waiting on async tasks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Written {
+    Partial,
+    Complete,
+}
+
 //Recursively render an entity, including its children
-pub(crate) fn render_entity_tags(
+pub(crate) fn render_entity_tags<W: Write>(
     world: &mut World,
     entity: Entity,
-) -> Result<Either<String, String>, std::fmt::Error> {
+    out: &mut W,
+) -> Result<Written, std::fmt::Error> {
     let render_tag: RenderTag = {
         world
             .get::<RenderTag>(entity)
             .expect("Entity should have a render tag by now")
             .clone()
     };
-    dbg!(entity, render_tag.clone());
+    // Waiting suspense boundaries stream their fallback, and get swapped in once ready.
+    // Once suspended, their content is only rendered by render_suspended
+    if world.get::<Suspended>(entity).is_some() {
+        return Ok(Written::Complete);
+    }
+    if let Some(suspense) = world.get::<Suspense>(entity) {
+        let untouched = !matches!(
+            render_tag,
+            RenderTag::Consumed | RenderTag::OpenConsumed { .. }
+        );
+        if untouched && is_waiting(world, entity) {
+            out.write_str(&render_placeholder(entity, suspense))?;
+            world.entity_mut(entity).insert(Suspended);
+            return Ok(Written::Complete);
+        }
+    }
     match render_tag {
-        RenderTag::Consumed => Ok(Either::Right("".to_string())),
-        RenderTag::OpenConsumed { close } => match render_children(entity, world)? {
-            Either::Left(partial) => Ok(Either::Left(partial)),
-            Either::Right(s) => {
-                let mut output = s;
+        RenderTag::Consumed => Ok(Written::Complete),
+        RenderTag::OpenConsumed { close } => match render_children(entity, world, out)? {
+            Written::Partial => Ok(Written::Partial),
+            Written::Complete => {
                 world.entity_mut(entity).insert(RenderTag::Consumed);
-                write!(output, "{}", close)?;
-                Ok(Either::Right(output))
+                out.write_str(&close)?;
+                Ok(Written::Complete)
             }
         },
-        RenderTag::Waiting => Ok(Either::Left("".to_string())),
-        RenderTag::SelfClosing(s) => {
-            world.entity_mut(entity).insert(RenderTag::Consumed);
-            Ok(Either::Right(s.to_string()))
-        }
-        RenderTag::Text(s) => {
+        RenderTag::Waiting => Ok(Written::Partial),
+        RenderTag::Void(s) | RenderTag::Text(s) => {
             world.entity_mut(entity).insert(RenderTag::Consumed);
-            Ok(Either::Right(s.to_string()))
+            out.write_str(&s)?;
+            Ok(Written::Complete)
         }
         RenderTag::OpenClose { open, close } => {
-            let mut output = open;
-            match render_children(entity, world)? {
-                Either::Left(partial) => {
-                    output.write_str(&partial)?;
+            if world.get::<Html>(entity).is_some()
+                && world.get_resource::<RenderMode>() == Some(&RenderMode::Document)
+            {
+                out.write_str("<!DOCTYPE html>")?;
+            }
+            out.write_str(&open)?;
+            if world.get::<Head>(entity).is_some() {
+                out.write_str(&take_pending_styles(world))?;
+            }
+            match render_children(entity, world, out)? {
+                Written::Partial => {
                     world
                         .entity_mut(entity)
                         .insert(RenderTag::OpenConsumed { close });
-                    Ok(Either::Left(output))
+                    Ok(Written::Partial)
                 }
-                Either::Right(s) => {
-                    output.push_str(&s);
+                Written::Complete => {
                     world.entity_mut(entity).insert(RenderTag::Consumed);
-                    write!(output, "{}", close)?;
-                    Ok(Either::Right(output))
+                    out.write_str(&close)?;
+                    Ok(Written::Complete)
                 }
             }
         }
     }
 }
 
-fn render_children(
+fn render_children<W: Write>(
     entity: Entity,
     world: &mut World,
-) -> Result<Either<String, String>, std::fmt::Error> {
-    let mut output = String::new();
-    let children: Option<Vec<Entity>> = {
+    out: &mut W,
+) -> Result<Written, std::fmt::Error> {
+    let children: Vec<Entity> = {
         world
             .get::<Children>(entity)
             .map(|c| c.iter().map(|e| e.to_owned()).collect::<Vec<_>>())
+            .unwrap_or_default()
     };
-    if let Some(children) = children {
-        for child in children.into_iter() {
-            match render_entity_tags(world, child)? {
-                Either::Left(partial) => {
-                    output.write_str(&partial)?;
-                    return Ok(Either::Left(output.to_string()));
-                }
-                Either::Right(s) => output.push_str(&s),
-            }
+    render_entities(world, children, out)
+}
+
+//Render a list of sibling entities in order, stopping at the first that is still partial
+fn render_entities<W: Write>(
+    world: &mut World,
+    entities: Vec<Entity>,
+    out: &mut W,
+) -> Result<Written, std::fmt::Error> {
+    for entity in entities.into_iter() {
+        if render_entity_tags(world, entity, out)? == Written::Partial {
+            return Ok(Written::Partial);
         }
     }
-    Ok(Either::Right(output))
+    Ok(Written::Complete)
+}
+
+/// Write everything that's ready to render, root by root in entity id order,
+/// followed by any suspense boundaries ready to be swapped in.
+/// Entity id order follows spawn order only until a despawned entity's id is reused
+/// Pending styles go first, unless head is still to be rendered and will take them
+pub(crate) fn write_output<W: Write>(
+    world: &mut World,
+    out: &mut W,
+) -> Result<Written, std::fmt::Error> {
+    let mut roots = world
+        .query_filtered::<Entity, (Or<(With<Tag>, With<Text>, With<RawHtml>)>, Without<Parent>)>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    roots.sort();
+    let head_pending = world
+        .query_filtered::<&RenderTag, With<Head>>()
+        .iter(world)
+        .any(|t| !matches!(t, RenderTag::Consumed | RenderTag::OpenConsumed { .. }));
+    if !head_pending {
+        out.write_str(&take_pending_styles(world))?;
+    }
+    let rendered = render_entities(world, roots, out)?;
+    let replacements = render_suspended(world, out)?;
+    Ok(match (rendered, replacements) {
+        (Written::Complete, Written::Complete) => Written::Complete,
+        _ => Written::Partial,
+    })
 }
 
 /// System to consume our tags into output resource
-/// Tries to find the root element via a query filter, and renders that
 pub(crate) fn render_tags_to_output(world: &mut World) {
-    let entity = world
-        .query_filtered::<Entity, (Or<(With<Tag>, With<Text>)>, Without<Parent>)>()
-        .single(world);
-    let rendered = render_entity_tags(world, entity).unwrap();
+    let mut chunk = String::new();
+    let written = write_output(world, &mut chunk).unwrap();
     let mut output = world.get_resource_mut::<RenderOutput>().unwrap();
-    output.0 = rendered;
+    output.0 = match written {
+        Written::Complete => Either::Right(chunk),
+        Written::Partial => Either::Left(chunk),
+    };
+}
+
+fn take_pending_styles(world: &mut World) -> String {
+    world
+        .get_resource_mut::<Stylesheet>()
+        .and_then(|mut stylesheet| stylesheet.take_pending())
+        .unwrap_or_default()
+}
+
+/// Render any suspended boundaries that are ready to be swapped in.
+/// Partial if there are still boundaries waiting, as the stream can't finish until they're sent
+fn render_suspended<W: Write>(world: &mut World, out: &mut W) -> Result<Written, std::fmt::Error> {
+    let mut suspended = world
+        .query_filtered::<Entity, With<Suspended>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    suspended.sort();
+    for entity in suspended {
+        if is_waiting(world, entity) {
+            continue;
+        }
+        world.entity_mut(entity).remove::<Suspended>();
+        out.write_str(&replacement_open(entity))?;
+        render_entity_tags(world, entity, out)?;
+        out.write_str(&replacement_close(entity))?;
+    }
+    // Boundaries nested in those just swapped in may have been suspended while rendering them
+    let waiting = world
+        .query_filtered::<(), With<Suspended>>()
+        .iter(world)
+        .next()
+        .is_some();
+    Ok(if waiting {
+        Written::Partial
+    } else {
+        Written::Complete
+    })
 }
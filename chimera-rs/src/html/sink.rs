@@ -0,0 +1,107 @@
+use std::{fmt, future::poll_fn, io, task::Poll};
+
+use bevy::{app::Main, prelude::*};
+use thiserror::Error;
+use tokio::runtime::{self, Handle};
+
+use crate::{node::AnyChimeraNode, r#async::AsyncTasks};
+
+use super::{
+    plugin::RenderHtmlPlugin,
+    render::{write_output, Written},
+};
+
+/// Set on worlds rendered through render_to_fmt or render_to_io,
+/// so the render system leaves tags for them to write instead of consuming them into RenderOutput
+#[derive(Resource, Debug, Clone, Copy)]
+pub(crate) struct ManualRender;
+
+#[derive(Error, Debug)]
+pub enum RenderError {
+    #[error(transparent)]
+    Fmt(#[from] fmt::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Everything up to content still waiting on async tasks was written.
+    /// Rendering again once they finish writes the rest
+    #[error("rendering stopped at content waiting on async tasks")]
+    Incomplete,
+}
+
+/// Update a world set up with RenderHtmlPlugin, such as an app's, and write its html straight into out.
+/// Use this in place of updating the app, as rendering consumes the tags it writes
+pub fn render_to_fmt<W: fmt::Write>(world: &mut World, out: &mut W) -> Result<(), RenderError> {
+    world.insert_resource(ManualRender);
+    world.run_schedule(Main);
+    world.clear_trackers();
+    match write_output(world, out)? {
+        Written::Complete => Ok(()),
+        Written::Partial => Err(RenderError::Incomplete),
+    }
+}
+
+/// Like render_to_fmt, but for byte sinks such as files, hashers and compressors
+pub fn render_to_io<W: io::Write>(world: &mut World, out: &mut W) -> Result<(), RenderError> {
+    let mut adapter = IoAdapter { out, error: None };
+    match render_to_fmt(world, &mut adapter) {
+        Err(RenderError::Fmt(_)) => Err(adapter
+            .error
+            .map(RenderError::Io)
+            .unwrap_or(RenderError::Fmt(fmt::Error))),
+        result => result,
+    }
+}
+
+/// Render a node on its own in a fresh app, returning its html
+pub fn render_to_string(node: impl Into<AnyChimeraNode>) -> String {
+    let mut app = App::new();
+    app.add_plugins(RenderHtmlPlugin);
+    node.into().spawn_with_world(&mut app.world);
+    render_app_to_string(&mut app)
+}
+
+/// Render an app set up with RenderHtmlPlugin to completion, returning its html.
+/// Async tasks are driven by a tokio runtime of the call's own, so they can use tokio's timers and io.
+/// Called from within a tokio runtime, rendering moves to a thread of its own,
+/// as blocking the runtime's thread could stop it driving the tasks
+pub fn render_app_to_string(app: &mut App) -> String {
+    if Handle::try_current().is_ok() {
+        return std::thread::scope(|scope| scope.spawn(|| render_app_to_string(app)).join())
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+    }
+    let runtime = runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to start a tokio runtime");
+    let _guard = runtime.enter();
+    let mut html = String::new();
+    loop {
+        match render_to_fmt(&mut app.world, &mut html) {
+            Err(RenderError::Incomplete) => {}
+            result => {
+                result.expect("Writing to a string can't fail");
+                return html;
+            }
+        }
+        let waker = app.world.resource::<AsyncTasks>().waker.clone();
+        runtime.block_on(poll_fn(|cx| match waker.register(cx.waker()) {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }));
+    }
+}
+
+/// Writes formatted output to an io sink, keeping the io error as fmt::Error can't carry it
+struct IoAdapter<'a, W: io::Write> {
+    out: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
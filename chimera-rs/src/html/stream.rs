@@ -7,16 +7,21 @@ use bevy::{prelude::*, tasks::futures_lite::Stream};
 use either::Either;
 use thiserror::Error;
 
-use crate::r#async::AsyncWaker;
+use crate::r#async::AsyncTasks;
 
 use super::render::RenderOutput;
 
 pub struct AppHtmlStream {
     app: App,
+    //Set when the app needs another update regardless of being woken, such as after a chunk
+    needs_update: bool,
 }
 impl AppHtmlStream {
     pub fn new(app: App) -> Self {
-        Self { app }
+        Self {
+            app,
+            needs_update: true,
+        }
     }
 }
 
@@ -27,16 +32,28 @@ impl Stream for AppHtmlStream {
     type Item = Result<String, Never>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.app
-            .insert_resource(AsyncWaker(Some(cx.waker().clone())));
+        //Only run the schedule if a task or world callback has woken us since last time
+        let woken = self
+            .app
+            .world
+            .resource::<AsyncTasks>()
+            .waker
+            .register(cx.waker());
+        if !woken && !self.needs_update {
+            return Poll::Pending;
+        }
+        self.needs_update = false;
         self.app.update();
-        let render_output = self.app.world.get_resource::<RenderOutput>().unwrap();
-        match render_output.0.clone() {
-            Either::Left(chunk) if chunk.len() == 0 => Poll::Pending, //TODO pass waker into waiting components to wake up
+        let mut render_output = self.app.world.resource_mut::<RenderOutput>();
+        let poll = match std::mem::replace(&mut render_output.0, Either::Left(String::new())) {
+            //Nothing to send until a task wakes us
+            Either::Left(chunk) if chunk.is_empty() => return Poll::Pending,
             Either::Left(chunk) => Poll::Ready(Some(Ok(chunk))),
             //Right means the stream is done
-            Either::Right(rest) if rest.len() == 0 => Poll::Ready(None),
+            Either::Right(rest) if rest.is_empty() => return Poll::Ready(None),
             Either::Right(rest) => Poll::Ready(Some(Ok(rest))),
-        }
+        };
+        self.needs_update = true;
+        poll
     }
 }
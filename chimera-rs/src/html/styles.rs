@@ -1,27 +1,304 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 use bevy::prelude::*;
 
 use super::attributes::RenderAttributes;
+use super::css::{
+    AlignItems, Color, Display, FlexDirection, FlexWrap, JustifyContent, Length, Overflow,
+    Position, TextAlign,
+};
+use super::escape::escape_css;
 
-#[derive(Component, Debug, Clone)]
-pub struct Styles(pub HashMap<&'static str, &'static str>);
+macro_rules! style_properties {
+    ($($variant:ident($value:ty) => $name:literal, $method:ident;)+) => {
+        /// A typed css property, lowered to a css declaration by apply_styles
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum StyleProperty {
+            $($variant($value),)+
+            /// Any property without a typed variant, as a name and value
+            Custom(Cow<'static, str>, Cow<'static, str>),
+            /// Properties applied while hovered. Only rendered in stylesheet mode
+            Hover(Vec<StyleProperty>),
+            /// Properties applied while focused. Only rendered in stylesheet mode
+            Focus(Vec<StyleProperty>),
+            /// Properties applied when the media query matches. Only rendered in stylesheet mode
+            Media(Cow<'static, str>, Vec<StyleProperty>),
+        }
 
-pub fn apply_styles(mut commands: Commands, query: Query<(Entity, &RenderAttributes, &Styles)>) {
+        impl StyleProperty {
+            /// The property as a css declaration such as color:red;, or None for nested variants
+            pub fn declaration(&self) -> Option<String> {
+                match self {
+                    $(StyleProperty::$variant(value) => {
+                        Some(format!("{}:{};", $name, escape_css(&value.to_string())))
+                    })+
+                    StyleProperty::Custom(name, value) => {
+                        Some(format!("{}:{};", escape_css(name), escape_css(value)))
+                    }
+                    StyleProperty::Hover(_) | StyleProperty::Focus(_) | StyleProperty::Media(..) => {
+                        None
+                    }
+                }
+            }
+        }
+
+        impl Styles {
+            $(
+                pub fn $method(mut self, value: impl Into<$value>) -> Self {
+                    self.0.push(StyleProperty::$variant(value.into()));
+                    self
+                }
+            )+
+        }
+    };
+}
+
+/// Styles for an entity, applied in order so later properties win
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct Styles(pub Vec<StyleProperty>);
+
+impl Styles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a property by name, for anything without a typed method
+    pub fn custom(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        value: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        self.0
+            .push(StyleProperty::Custom(name.into(), value.into()));
+        self
+    }
+
+    pub fn hover(mut self, styles: Styles) -> Self {
+        self.0.push(StyleProperty::Hover(styles.0));
+        self
+    }
+
+    pub fn focus(mut self, styles: Styles) -> Self {
+        self.0.push(StyleProperty::Focus(styles.0));
+        self
+    }
+
+    pub fn media(mut self, query: impl Into<Cow<'static, str>>, styles: Styles) -> Self {
+        self.0.push(StyleProperty::Media(query.into(), styles.0));
+        self
+    }
+
+    /// The properties as css declarations, such as color:red;, leaving out nested variants
+    pub fn declarations(&self) -> String {
+        declarations(&self.0)
+    }
+
+    /// The generated class name for this style set, the same for every entity with equal styles
+    /// and stable across builds. Stylesheet adds a suffix in the unlikely case two sets collide
+    pub fn class_name(&self) -> String {
+        format!("chimera-{:016x}", fnv1a(self.rules("").as_bytes()))
+    }
+
+    /// The css rules for this style set under the given selector, including its variants
+    pub fn rules(&self, selector: &str) -> String {
+        rules(selector, &self.0)
+    }
+}
+
+/// 64 bit FNV-1a, which unlike std's hashers is fixed across Rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn declarations(properties: &[StyleProperty]) -> String {
+    properties.iter().filter_map(|p| p.declaration()).collect()
+}
+
+fn rules(selector: &str, properties: &[StyleProperty]) -> String {
+    let mut output = String::new();
+    let declarations = declarations(properties);
+    if !declarations.is_empty() {
+        output.push_str(&format!("{}{{{}}}", selector, declarations));
+    }
+    for property in properties {
+        match property {
+            StyleProperty::Hover(inner) => {
+                output.push_str(&rules(&format!("{}:hover", selector), inner))
+            }
+            StyleProperty::Focus(inner) => {
+                output.push_str(&rules(&format!("{}:focus", selector), inner))
+            }
+            StyleProperty::Media(query, inner) => output.push_str(&format!(
+                "@media {}{{{}}}",
+                escape_css(query),
+                rules(selector, inner)
+            )),
+            _ => {}
+        }
+    }
+    output
+}
+
+/// How Styles are rendered. Inline puts them in each entity's style attribute,
+/// Stylesheet gives each distinct style set a generated class with its rules in a style block
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StyleMode {
+    #[default]
+    Inline,
+    Stylesheet,
+}
+
+/// The rules generated in stylesheet mode, in the order their style sets were first seen.
+/// The style block is placed in head when it renders. Rules added once head has been streamed,
+/// or on pages without a head, are sent in a style block ahead of the next chunk
+#[derive(Resource, Debug, Default)]
+pub struct Stylesheet {
+    /// Each generated class, with the rules of the style set it was generated for
+    classes: HashMap<String, String>,
+    rules: Vec<String>,
+    emitted: usize,
+}
+
+impl Stylesheet {
+    /// All the generated css, to serve as a separate asset
+    pub fn css(&self) -> String {
+        self.rules.concat()
+    }
+
+    fn add(&mut self, styles: &Styles) -> String {
+        let name = styles.class_name();
+        let rules = styles.rules("");
+        let mut class = name.clone();
+        for suffix in 1.. {
+            match self.classes.get(&class) {
+                Some(existing) if *existing == rules => break,
+                Some(_) => class = format!("{}-{}", name, suffix),
+                None => {
+                    self.rules.push(styles.rules(&format!(".{}", class)));
+                    self.classes.insert(class.clone(), rules);
+                    break;
+                }
+            }
+        }
+        class
+    }
+
+    /// A style block of the rules not yet rendered, if there are any
+    pub(crate) fn take_pending(&mut self) -> Option<String> {
+        if self.emitted == self.rules.len() {
+            return None;
+        }
+        let pending = self.rules[self.emitted..].concat();
+        self.emitted = self.rules.len();
+        Some(format!("<style>{}</style>", pending))
+    }
+}
+
+style_properties! {
+    Display(Display) => "display", display;
+    Position(Position) => "position", position;
+    Top(Length) => "top", top;
+    Right(Length) => "right", right;
+    Bottom(Length) => "bottom", bottom;
+    Left(Length) => "left", left;
+    Width(Length) => "width", width;
+    Height(Length) => "height", height;
+    MinWidth(Length) => "min-width", min_width;
+    MinHeight(Length) => "min-height", min_height;
+    MaxWidth(Length) => "max-width", max_width;
+    MaxHeight(Length) => "max-height", max_height;
+    Margin(Length) => "margin", margin;
+    MarginTop(Length) => "margin-top", margin_top;
+    MarginRight(Length) => "margin-right", margin_right;
+    MarginBottom(Length) => "margin-bottom", margin_bottom;
+    MarginLeft(Length) => "margin-left", margin_left;
+    Padding(Length) => "padding", padding;
+    PaddingTop(Length) => "padding-top", padding_top;
+    PaddingRight(Length) => "padding-right", padding_right;
+    PaddingBottom(Length) => "padding-bottom", padding_bottom;
+    PaddingLeft(Length) => "padding-left", padding_left;
+    Overflow(Overflow) => "overflow", overflow;
+    FlexDirection(FlexDirection) => "flex-direction", flex_direction;
+    FlexWrap(FlexWrap) => "flex-wrap", flex_wrap;
+    FlexGrow(f32) => "flex-grow", flex_grow;
+    FlexShrink(f32) => "flex-shrink", flex_shrink;
+    FlexBasis(Length) => "flex-basis", flex_basis;
+    JustifyContent(JustifyContent) => "justify-content", justify_content;
+    AlignItems(AlignItems) => "align-items", align_items;
+    Gap(Length) => "gap", gap;
+    GridTemplateColumns(String) => "grid-template-columns", grid_template_columns;
+    GridTemplateRows(String) => "grid-template-rows", grid_template_rows;
+    GridColumn(String) => "grid-column", grid_column;
+    GridRow(String) => "grid-row", grid_row;
+    Color(Color) => "color", color;
+    BackgroundColor(Color) => "background-color", background_color;
+    BorderColor(Color) => "border-color", border_color;
+    BorderWidth(Length) => "border-width", border_width;
+    BorderRadius(Length) => "border-radius", border_radius;
+    FontSize(Length) => "font-size", font_size;
+    FontWeight(u16) => "font-weight", font_weight;
+    LineHeight(f32) => "line-height", line_height;
+    TextAlign(TextAlign) => "text-align", text_align;
+    Opacity(f32) => "opacity", opacity;
+}
+
+pub fn apply_styles(
+    mut commands: Commands,
+    mode: Res<StyleMode>,
+    mut stylesheet: ResMut<Stylesheet>,
+    query: Query<(Entity, &RenderAttributes, &Styles)>,
+) {
     for (entity, render_attributes, style) in &query {
         let mut new_attributes = render_attributes.0.clone();
-        let added_style = style
-            .0
-            .iter()
-            .map(|(k, v)| format!("{}:{};", k, v))
-            .collect::<Vec<_>>()
-            .join("");
-        new_attributes
-            .entry("style".to_string())
-            .or_default()
-            .push_str(&added_style);
+        match *mode {
+            StyleMode::Inline => {
+                let attribute = new_attributes.entry("style".to_string()).or_default();
+                // A style attribute set by hand may leave off its last semicolon
+                if !attribute.trim_end().is_empty() && !attribute.trim_end().ends_with(';') {
+                    attribute.push(';');
+                }
+                attribute.push_str(&style.declarations());
+            }
+            StyleMode::Stylesheet => {
+                let class = stylesheet.add(style);
+                let classes = new_attributes.entry("class".to_string()).or_default();
+                if !classes.is_empty() {
+                    classes.push(' ');
+                }
+                classes.push_str(&class);
+            }
+        }
         commands
             .entity(entity)
             .insert(RenderAttributes(new_attributes));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn class_names_are_stable() {
+        assert_eq!(
+            Styles::new().color("red").class_name(),
+            "chimera-082a9e301dfcab58"
+        );
+    }
+
+    #[test]
+    fn colliding_class_names_get_a_suffix() {
+        let styles = Styles::new().color("red");
+        let mut stylesheet = Stylesheet::default();
+        stylesheet
+            .classes
+            .insert(styles.class_name(), "{color:blue;}".to_string());
+        let class = stylesheet.add(&styles);
+        assert_eq!(class, format!("{}-1", styles.class_name()));
+        assert_eq!(stylesheet.add(&styles), class);
+        assert_eq!(stylesheet.css(), format!(".{}{{color:red;}}", class));
+    }
+}
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+
+use super::render::RenderTag;
+
+/// Marks an entity as a suspense boundary. While anything beneath it is waiting on async tasks,
+/// the fallback markup is streamed in its place and the rest of the page carries on rendering.
+/// Once ready, the real content is streamed in a template and swapped in by a small script
+#[derive(Component, Debug, Clone)]
+pub struct Suspense(pub String);
+
+/// A suspense boundary whose placeholder has been streamed, but whose content hasn't
+#[derive(Component, Debug, Clone)]
+pub(crate) struct Suspended;
+
+/// Whether the entity or anything beneath it is waiting, not counting nested suspense boundaries
+pub(crate) fn is_waiting(world: &World, entity: Entity) -> bool {
+    match world.get::<RenderTag>(entity) {
+        None | Some(RenderTag::Waiting) => return true,
+        _ => {}
+    }
+    world.get::<Children>(entity).is_some_and(|children| {
+        children
+            .iter()
+            .filter(|child| world.get::<Suspense>(**child).is_none())
+            .any(|child| is_waiting(world, *child))
+    })
+}
+
+fn placeholder_id(entity: Entity) -> String {
+    format!(
+        "chimera-suspense-{}v{}",
+        entity.index(),
+        entity.generation()
+    )
+}
+
+/// Placeholder streamed in place of a waiting suspense boundary
+pub(crate) fn render_placeholder(entity: Entity, fallback: &Suspense) -> String {
+    format!(
+        "<chimera-suspense id=\"{}\">{}</chimera-suspense>",
+        placeholder_id(entity),
+        fallback.0
+    )
+}
+
+/// Opens the template holding a suspense boundary's content
+pub(crate) fn replacement_open(entity: Entity) -> String {
+    format!("<template id=\"{}-content\">", placeholder_id(entity))
+}
+
+/// Closes a suspense boundary's template, along with the script to swap it in for its placeholder
+pub(crate) fn replacement_close(entity: Entity) -> String {
+    let id = placeholder_id(entity);
+    format!(
+        "</template>\
+        <script>(function(){{\
+        var t=document.getElementById(\"{id}-content\");\
+        document.getElementById(\"{id}\").replaceWith(t.content);\
+        t.remove();\
+        document.currentScript.remove();\
+        }})()</script>"
+    )
+}
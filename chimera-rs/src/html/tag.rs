@@ -1,12 +1,12 @@
-use bevy::prelude::*;
+use bevy::{ecs::schedule::SystemConfigs, prelude::*};
 
 macro_rules! make_tag {
     ($name:ident, $tag:literal) => {
         #[derive(Component, Debug, Clone, Copy)]
         pub struct $name;
 
-        impl Into<Tag> for $name {
-            fn into(self) -> Tag {
+        impl From<$name> for Tag {
+            fn from(_: $name) -> Tag {
                 Tag($tag)
             }
         }
@@ -27,6 +27,60 @@ pub fn make_tag<T: Into<Tag> + Component + Copy>(
     }
 }
 
+macro_rules! tag_systems {
+    ($(($($tag:ident),+)),+) => {
+        ($(($(make_tag::<$tag>),+),)+)
+    };
+}
+
+/// The systems adding Tag to every shorthand component, for renderers to run before they render
+pub(crate) fn tag_systems() -> SystemConfigs {
+    tag_systems!(
+        (Div, H1, H2, H3, H4, H5, H6, P, Span, A),
+        (Img, Button, Input, Label, Select, Option, Textarea, Form, Table, Tr),
+        (Td, Th, Thead, Tbody, Tfoot, Ul, Ol, Li, Dl, Dt),
+        (Dd, Section, Header, Footer, Main, Article, Aside, Nav, Address, Blockquote),
+        (Details, Summary, Dialog, Menu, MenuItem, Figure, Figcaption, Audio, Video),
+        (
+            Canvas, Embed, Object, Source, Track, Map, Area, Math, Svg, Iframe, Frame, Frameset,
+            Noframes
+        ),
+        (
+            B, Strong, I, Em, Mark, Small, Del, Ins, Sub, Sup, Pre, Code, Var, Samp, Kbd, Q, Cite,
+            Abbr
+        ),
+        (Dfn, Time, Progress, Meter, Br, Wbr, Template, Slot, Script, Noscript, Style, Meta),
+        (Link, Title, Base, Head, Html, Body, Hr, Col)
+    )
+    .into_configs()
+}
+
+/// How an element is laid out by the renderers other than html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ElementKind {
+    /// Starts on its own line, with its children laid out in a column
+    Block,
+    /// Flows along with the text around it
+    Inline,
+    /// Isn't content, so is left out
+    Hidden,
+}
+
+impl ElementKind {
+    pub(crate) fn of(tag: &str) -> Self {
+        match tag {
+            "head" | "title" | "base" | "meta" | "link" | "script" | "noscript" | "style"
+            | "template" => ElementKind::Hidden,
+            "html" | "body" | "div" | "p" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "section"
+            | "header" | "footer" | "main" | "article" | "aside" | "nav" | "form" | "fieldset"
+            | "legend" | "address" | "details" | "summary" | "dialog" | "figure" | "figcaption"
+            | "blockquote" | "pre" | "hr" | "ul" | "ol" | "menu" | "li" | "dl" | "dt" | "dd"
+            | "table" | "thead" | "tbody" | "tfoot" | "tr" | "center" => ElementKind::Block,
+            _ => ElementKind::Inline,
+        }
+    }
+}
+
 make_tag!(Div, "div");
 make_tag!(H1, "h1");
 make_tag!(H2, "h2");
@@ -125,3 +179,5 @@ make_tag!(Base, "base");
 make_tag!(Head, "head");
 make_tag!(Html, "html");
 make_tag!(Body, "body");
+make_tag!(Hr, "hr");
+make_tag!(Col, "col");
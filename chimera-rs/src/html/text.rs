@@ -2,3 +2,9 @@ use bevy::prelude::*;
 
 #[derive(Component, Debug, Clone)]
 pub struct Text(pub String);
+
+/// Markup that is rendered verbatim, without escaping.
+/// On a tagged entity it becomes the inner markup of that tag, such as the body of a script.
+/// Only use this for trusted content
+#[derive(Component, Debug, Clone)]
+pub struct RawHtml(pub String);
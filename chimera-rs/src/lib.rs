@@ -2,5 +2,11 @@ pub mod r#async;
 pub mod axum_html;
 pub mod html;
 pub mod logic;
+pub mod markdown;
 pub mod node;
+pub mod plain_text;
 pub mod prelude;
+pub mod terminal;
+mod text_layout;
+#[cfg(feature = "bevy_ui")]
+pub mod ui;
@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+
+use crate::{
+    html::tag::tag_systems,
+    text_layout::{raw_text, roots, TextLayout, TextWriter},
+};
+
+/// The entity tree rendered as markdown, rebuilt every update
+#[derive(Resource, Debug, Default, Clone)]
+pub struct MarkdownOutput(pub String);
+
+#[derive(Debug, Hash, Eq, PartialEq, Clone, SystemSet)]
+enum MarkdownRenderSet {
+    ApplyTags,
+    Render,
+}
+
+/// Renders the same entity trees as RenderHtmlPlugin as CommonMark into MarkdownOutput.
+/// Tables are written as GitHub flavoured pipe tables, as CommonMark has none.
+/// To render an app that already uses RenderHtmlPlugin, call render_markdown on its world instead
+pub struct RenderMarkdownPlugin;
+impl Plugin for RenderMarkdownPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, tag_systems().in_set(MarkdownRenderSet::ApplyTags));
+        app.add_systems(
+            PostUpdate,
+            render_markdown_output.in_set(MarkdownRenderSet::Render),
+        );
+        app.init_resource::<MarkdownOutput>();
+    }
+}
+
+fn render_markdown_output(world: &mut World) {
+    let output = render_markdown(world);
+    world.resource_mut::<MarkdownOutput>().0 = output;
+}
+
+/// Render every root entity as markdown, in entity id order
+pub fn render_markdown(world: &mut World) -> String {
+    let roots = roots(world);
+    let mut writer = Writer::new(world);
+    for root in roots {
+        writer.entity(root);
+    }
+    writer.finish()
+}
+
+struct Writer<'w> {
+    layout: TextLayout<'w>,
+    strong: usize,
+    emphasis: usize,
+}
+
+impl<'w> Writer<'w> {
+    fn new(world: &'w World) -> Self {
+        Self {
+            layout: TextLayout::new(world),
+            strong: 0,
+            emphasis: 0,
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        self.layout.into_string()
+    }
+
+    /// Wrap inline content in a delimiter such as **, leaving it out for nested or empty content
+    fn delimited(&mut self, entity: Entity, delimiter: &str, depth: fn(&mut Self) -> &mut usize) {
+        *depth(self) += 1;
+        let outermost = *depth(self) == 1;
+        let start = self.layout.line.len();
+        if outermost {
+            self.layout.line.push_str(delimiter);
+        }
+        self.render_children(entity);
+        *depth(self) -= 1;
+        if !outermost {
+            return;
+        }
+        if self.layout.line[start + delimiter.len()..]
+            .trim()
+            .is_empty()
+        {
+            self.layout.line.truncate(start);
+        } else {
+            self.layout.line.push_str(delimiter);
+        }
+    }
+
+    /// A fenced code block, with its language taken from a language-* class on it or its code
+    fn code_block(&mut self, entity: Entity) {
+        self.blank_line();
+        let code = raw_text(self.layout.world, entity);
+        let language = [entity]
+            .into_iter()
+            .chain(self.layout.children(entity))
+            .filter_map(|e| self.layout.attribute(e, "class"))
+            .flat_map(|class| class.split_whitespace())
+            .find_map(|class| class.strip_prefix("language-"))
+            .unwrap_or_default();
+        let fence = "`".repeat(longest_run(&code, '`').max(2) + 1);
+        self.layout.push_line(&format!("{}{}", fence, language));
+        for line in code.trim_end_matches('\n').lines() {
+            self.layout.push_line(line);
+        }
+        self.layout.push_line(&fence);
+        self.blank_line();
+    }
+
+    /// A pipe table, with the first row as its header
+    fn table(&mut self, entity: Entity) {
+        self.blank_line();
+        let rows = self
+            .layout
+            .rows(entity)
+            .into_iter()
+            .map(|row| self.row(row))
+            .collect::<Vec<_>>();
+        let columns = rows.iter().map(|r| r.len()).max().unwrap_or_default();
+        for (index, mut row) in rows.into_iter().enumerate() {
+            row.resize(columns, String::new());
+            self.layout.push_line(&format!("| {} |", row.join(" | ")));
+            if index == 0 {
+                self.layout
+                    .push_line(&format!("|{}", " --- |".repeat(columns)));
+            }
+        }
+        self.blank_line();
+    }
+
+    /// A row's cells, each laid out on a single line
+    fn row(&self, row: Entity) -> Vec<String> {
+        self.layout
+            .cells(row)
+            .into_iter()
+            .map(|cell| {
+                let mut writer = Writer::new(self.layout.world);
+                writer.render_children(cell);
+                writer.finish().replace('\n', " ").replace('|', "\\|")
+            })
+            .collect()
+    }
+}
+
+impl<'w> TextWriter<'w> for Writer<'w> {
+    fn layout(&mut self) -> &mut TextLayout<'w> {
+        &mut self.layout
+    }
+
+    fn flush(&mut self) {
+        let line = std::mem::take(&mut self.layout.line);
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !line.is_empty() {
+            self.layout.push_line(&line);
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        let text = escape(text);
+        if !self.layout.line.trim().is_empty() {
+            self.layout.line.push_str(&text);
+            return;
+        }
+        // Text starting a line could be read as a heading, quote or list item
+        let text = text.trim_start();
+        if text.starts_with(['#', '>', '-', '+']) {
+            self.layout.line.push('\\');
+        }
+        self.layout.line.push_str(text);
+    }
+
+    fn element(&mut self, entity: Entity, tag: &'static str) -> bool {
+        match tag {
+            "br" => {
+                self.layout.line.push('\\');
+                self.flush();
+            }
+            "hr" => {
+                self.blank_line();
+                self.layout.push_line("---");
+                self.blank_line();
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.blank_line();
+                let level = tag[1..].parse().unwrap_or(1);
+                self.layout
+                    .line
+                    .push_str(&format!("{} ", "#".repeat(level)));
+                self.render_children(entity);
+                self.blank_line();
+            }
+            "p" => {
+                self.blank_line();
+                self.render_children(entity);
+                self.blank_line();
+            }
+            "b" | "strong" => self.delimited(entity, "**", |w| &mut w.strong),
+            "i" | "em" | "cite" | "var" => self.delimited(entity, "*", |w| &mut w.emphasis),
+            "code" | "kbd" | "samp" => {
+                let code = code_span(&raw_text(self.layout.world, entity));
+                self.layout.line.push_str(&code);
+            }
+            "a" => match self.layout.attribute(entity, "href") {
+                Some(href) => {
+                    self.layout.line.push('[');
+                    self.render_children(entity);
+                    self.layout
+                        .line
+                        .push_str(&format!("]({})", destination(href)));
+                }
+                None => self.render_children(entity),
+            },
+            "img" => {
+                let alt = self.layout.attribute(entity, "alt").unwrap_or_default();
+                let src = self.layout.attribute(entity, "src").unwrap_or_default();
+                self.layout
+                    .line
+                    .push_str(&format!("![{}]({})", escape(alt), destination(src)));
+            }
+            "pre" => self.code_block(entity),
+            "ul" | "ol" | "menu" => self.list(entity, tag == "ol", "- "),
+            "blockquote" => {
+                self.blank_line();
+                self.indented(entity, "> ");
+                self.blank_line();
+            }
+            "table" => self.table(entity),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Escape characters that would otherwise be read as markdown
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A link destination, in angle brackets if it has spaces or parentheses
+fn destination(url: &str) -> String {
+    if url.contains([' ', '(', ')']) {
+        format!("<{}>", url)
+    } else {
+        url.to_string()
+    }
+}
+
+/// Inline code, with enough backticks around it to contain any within
+fn code_span(code: &str) -> String {
+    let ticks = "`".repeat(longest_run(code, '`') + 1);
+    let padding = if code.starts_with('`') || code.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{ticks}{padding}{code}{padding}{ticks}")
+}
+
+/// The longest run of a character in text
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|t| t != c)
+        .map(|run| run.len())
+        .max()
+        .unwrap_or_default()
+}
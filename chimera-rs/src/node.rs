@@ -1,20 +1,73 @@
-use std::rc::Rc;
-
-use bevy::{ecs::system::EntityCommands, prelude::*};
-use futures::task::Spawn;
+use bevy::prelude::*;
 
+/// Spawns child_nodes if condition is true, otherwise else_child_nodes
 pub struct IfNode<F> {
     pub condition: F,
     pub child_nodes: Vec<AnyChimeraNode>,
+    pub else_child_nodes: Vec<AnyChimeraNode>,
 }
 pub struct EntityNode<B> {
     pub bundle: B,
     pub child_nodes: Vec<AnyChimeraNode>,
 }
+/// Spawns one set of child nodes per item of the iterator it was built from
+pub struct ForNode {
+    pub child_nodes: Vec<Vec<AnyChimeraNode>>,
+}
+
+/// Spawns the child nodes of the arm that matched when it was built
+pub struct MatchNode {
+    pub child_nodes: Vec<AnyChimeraNode>,
+}
+
+/// Spawns its child nodes in place, without an entity of its own
+pub struct FragmentNode {
+    pub child_nodes: Vec<AnyChimeraNode>,
+}
+
+/// A reusable template, used in chimera! as an uppercase tag such as <Card title="x">.
+/// The tag's attributes set the struct's fields, and its child nodes are passed to render,
+/// where they can be placed with #{children}
+pub trait ChimeraComponent {
+    fn render(self, children: Vec<AnyChimeraNode>) -> AnyChimeraNode;
+}
 
 pub enum AnyChimeraNode {
     Entity(Box<dyn AnyEntityNode>),
     If(Box<dyn AnyIfNode>),
+    For(Box<dyn AnyForNode>),
+    Match(Box<dyn AnyMatchNode>),
+    Fragment(Box<dyn AnyFragmentNode>),
+}
+
+impl<B: Bundle + Clone> From<EntityNode<B>> for AnyChimeraNode {
+    fn from(node: EntityNode<B>) -> Self {
+        AnyChimeraNode::Entity(Box::new(node))
+    }
+}
+
+impl<F: Fn() -> bool + 'static> From<IfNode<F>> for AnyChimeraNode {
+    fn from(node: IfNode<F>) -> Self {
+        AnyChimeraNode::If(Box::new(node))
+    }
+}
+
+impl From<ForNode> for AnyChimeraNode {
+    fn from(node: ForNode) -> Self {
+        AnyChimeraNode::For(Box::new(node))
+    }
+}
+
+impl From<MatchNode> for AnyChimeraNode {
+    fn from(node: MatchNode) -> Self {
+        AnyChimeraNode::Match(Box::new(node))
+    }
+}
+
+impl From<FragmentNode> for AnyChimeraNode {
+    fn from(node: FragmentNode) -> Self {
+        AnyChimeraNode::Fragment(Box::new(node))
+    }
 }
 
 macro_rules! define_node_trait {
@@ -33,22 +86,44 @@ macro_rules! define_node_trait {
 
 define_node_trait!(AnyEntityNode, Entity);
 define_node_trait!(AnyIfNode, Option<Vec<Entity>>);
+define_node_trait!(AnyForNode, Vec<Entity>);
+define_node_trait!(AnyMatchNode, Vec<Entity>);
+define_node_trait!(AnyFragmentNode, Vec<Entity>);
+
+macro_rules! impl_any_node_spawn {
+    ($name:ident, $builder:ident, $builder_type: ident) => {
+        pub fn $name(&self, $builder: &mut $builder_type) -> Vec<Entity> {
+            match self {
+                AnyChimeraNode::Entity(c) => vec![c.$name($builder)],
+                AnyChimeraNode::If(c) => c.$name($builder).unwrap_or_default(),
+                AnyChimeraNode::For(c) => c.$name($builder),
+                AnyChimeraNode::Match(c) => c.$name($builder),
+                AnyChimeraNode::Fragment(c) => c.$name($builder),
+            }
+        }
+    };
+}
+
+/// Spawn whichever kind of node this is, returning the top level entities spawned
+impl AnyChimeraNode {
+    impl_any_node_spawn!(spawn, commands, Commands);
+    impl_any_node_spawn!(spawn_with_child_builder, child_builder, ChildBuilder);
+    impl_any_node_spawn!(spawn_with_world, world, World);
+    impl_any_node_spawn!(
+        spawn_with_world_child_builder,
+        child_builder,
+        WorldChildBuilder
+    );
+}
 
 macro_rules! impl_entity_node_spawn {
     ($name:ident, $builder:ident, $builder_type: ident, $child_builder_func: ident) => {
         fn $name(&self, $builder: &mut $builder_type) -> Entity {
             let mut entity = $builder.spawn(self.bundle.clone());
-            if self.child_nodes.len() > 0 {
+            if !self.child_nodes.is_empty() {
                 entity.with_children(|builder| {
                     for child in &self.child_nodes {
-                        match child {
-                            AnyChimeraNode::Entity(c) => {
-                                c.$child_builder_func(builder);
-                            }
-                            AnyChimeraNode::If(c) => {
-                                c.$child_builder_func(builder);
-                            }
-                        }
+                        child.$child_builder_func(builder);
                     }
                 });
             }
@@ -82,19 +157,14 @@ impl<T: Bundle + Clone> AnyEntityNode for EntityNode<T> {
 macro_rules! impl_if_node_spawn {
     ($name:ident, $builder:ident, $builder_type: ident) => {
         fn $name(&self, $builder: &mut $builder_type) -> Option<Vec<Entity>> {
-            if (self.condition)() {
-                Some(
-                    self.child_nodes
-                        .iter()
-                        .flat_map(|c| match c {
-                            AnyChimeraNode::Entity(c) => vec![c.$name($builder)],
-                            AnyChimeraNode::If(c) => c.$name($builder).unwrap_or_default(),
-                        })
-                        .collect(),
-                )
+            let branch = if (self.condition)() {
+                &self.child_nodes
+            } else if !self.else_child_nodes.is_empty() {
+                &self.else_child_nodes
             } else {
-                None
-            }
+                return None;
+            };
+            Some(branch.iter().flat_map(|c| c.$name($builder)).collect())
         }
     };
 }
@@ -109,3 +179,59 @@ impl<F: Fn() -> bool> AnyIfNode for IfNode<F> {
         WorldChildBuilder
     );
 }
+
+macro_rules! impl_for_node_spawn {
+    ($name:ident, $builder:ident, $builder_type: ident) => {
+        fn $name(&self, $builder: &mut $builder_type) -> Vec<Entity> {
+            self.child_nodes
+                .iter()
+                .flatten()
+                .flat_map(|c| c.$name($builder))
+                .collect()
+        }
+    };
+}
+
+impl AnyForNode for ForNode {
+    impl_for_node_spawn!(spawn, commands, Commands);
+    impl_for_node_spawn!(spawn_with_child_builder, child_builder, ChildBuilder);
+    impl_for_node_spawn!(spawn_with_world, world, World);
+    impl_for_node_spawn!(
+        spawn_with_world_child_builder,
+        child_builder,
+        WorldChildBuilder
+    );
+}
+
+macro_rules! impl_child_nodes_spawn {
+    ($name:ident, $builder:ident, $builder_type: ident) => {
+        fn $name(&self, $builder: &mut $builder_type) -> Vec<Entity> {
+            self.child_nodes
+                .iter()
+                .flat_map(|c| c.$name($builder))
+                .collect()
+        }
+    };
+}
+
+impl AnyMatchNode for MatchNode {
+    impl_child_nodes_spawn!(spawn, commands, Commands);
+    impl_child_nodes_spawn!(spawn_with_child_builder, child_builder, ChildBuilder);
+    impl_child_nodes_spawn!(spawn_with_world, world, World);
+    impl_child_nodes_spawn!(
+        spawn_with_world_child_builder,
+        child_builder,
+        WorldChildBuilder
+    );
+}
+
+impl AnyFragmentNode for FragmentNode {
+    impl_child_nodes_spawn!(spawn, commands, Commands);
+    impl_child_nodes_spawn!(spawn_with_child_builder, child_builder, ChildBuilder);
+    impl_child_nodes_spawn!(spawn_with_world, world, World);
+    impl_child_nodes_spawn!(
+        spawn_with_world_child_builder,
+        child_builder,
+        WorldChildBuilder
+    );
+}
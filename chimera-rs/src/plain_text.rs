@@ -0,0 +1,260 @@
+use bevy::prelude::*;
+
+use crate::{
+    html::tag::tag_systems,
+    text_layout::{raw_text, roots, TextLayout, TextWriter},
+};
+
+/// How plain text is laid out
+#[derive(Resource, Debug, Clone)]
+pub struct PlainTextOptions {
+    /// Column paragraphs are wrapped at, and the width of horizontal rules
+    pub width: usize,
+}
+
+impl Default for PlainTextOptions {
+    fn default() -> Self {
+        Self { width: 72 }
+    }
+}
+
+/// The entity tree rendered as plain text, rebuilt every update
+#[derive(Resource, Debug, Default, Clone)]
+pub struct PlainTextOutput(pub String);
+
+#[derive(Debug, Hash, Eq, PartialEq, Clone, SystemSet)]
+enum PlainTextRenderSet {
+    ApplyTags,
+    Render,
+}
+
+/// Renders the same entity trees as RenderHtmlPlugin as plain text into PlainTextOutput,
+/// such as for the text part of an email.
+/// Paragraphs are wrapped, headings underlined, and links numbered with their urls listed at the end.
+/// To render an app that already uses RenderHtmlPlugin, call render_plain_text on its world instead
+pub struct RenderPlainTextPlugin;
+impl Plugin for RenderPlainTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, tag_systems().in_set(PlainTextRenderSet::ApplyTags));
+        app.add_systems(
+            PostUpdate,
+            render_plain_text_output.in_set(PlainTextRenderSet::Render),
+        );
+        app.init_resource::<PlainTextOptions>();
+        app.init_resource::<PlainTextOutput>();
+    }
+}
+
+fn render_plain_text_output(world: &mut World) {
+    let output = render_plain_text(world);
+    world.resource_mut::<PlainTextOutput>().0 = output;
+}
+
+/// Render every root entity as plain text, in entity id order
+pub fn render_plain_text(world: &mut World) -> String {
+    let roots = roots(world);
+    let width = world
+        .get_resource::<PlainTextOptions>()
+        .cloned()
+        .unwrap_or_default()
+        .width;
+    let mut writer = Writer::new(world, width);
+    for root in roots {
+        writer.entity(root);
+    }
+    writer.finish()
+}
+
+struct Writer<'w> {
+    layout: TextLayout<'w>,
+    width: usize,
+    /// Link urls, listed as footnotes at the end
+    links: Vec<String>,
+}
+
+impl<'w> Writer<'w> {
+    fn new(world: &'w World, width: usize) -> Self {
+        Self {
+            layout: TextLayout::new(world),
+            width,
+            links: vec![],
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        self.layout.trim_blank_lines();
+        if !self.links.is_empty() {
+            self.layout.lines.push(String::new());
+            for (index, link) in self.links.iter().enumerate() {
+                self.layout.lines.push(format!("[{}] {}", index + 1, link));
+            }
+        }
+        self.layout.lines.join("\n")
+    }
+
+    /// Link text is followed by a footnote number, unless it's the url itself
+    fn link(&mut self, entity: Entity) {
+        let start = self.layout.line.len();
+        self.render_children(entity);
+        let Some(href) = self.layout.attribute(entity, "href") else {
+            return;
+        };
+        if href.is_empty() || href.starts_with('#') || self.layout.line[start..].trim() == href {
+            return;
+        }
+        let number = match self.links.iter().position(|l| l == href) {
+            Some(index) => index + 1,
+            None => {
+                self.links.push(href.to_string());
+                self.links.len()
+            }
+        };
+        self.layout.line.push_str(&format!(" [{}]", number));
+    }
+
+    /// Lay out rows of cells in columns, padded to the widest cell of each
+    fn table(&mut self, entity: Entity) {
+        self.blank_line();
+        let rows = self
+            .layout
+            .rows(entity)
+            .into_iter()
+            .map(|row| self.row(row))
+            .collect::<Vec<_>>();
+        let columns = rows.iter().map(|r| r.len()).max().unwrap_or_default();
+        let widths = (0..columns)
+            .map(|c| {
+                rows.iter()
+                    .filter_map(|r| r.get(c))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        for row in rows {
+            let cells = row
+                .iter()
+                .enumerate()
+                .map(|(c, cell)| format!("{:width$}", cell, width = widths[c]))
+                .collect::<Vec<_>>();
+            self.layout.push_line(&cells.join("  "));
+        }
+        self.blank_line();
+    }
+
+    /// A row's cells, each laid out on a single unwrapped line, sharing link footnotes with the page
+    fn row(&mut self, row: Entity) -> Vec<String> {
+        self.layout
+            .cells(row)
+            .into_iter()
+            .map(|cell| {
+                let mut writer = Writer::new(self.layout.world, usize::MAX);
+                writer.links = std::mem::take(&mut self.links);
+                writer.render_children(cell);
+                writer.flush();
+                self.links = std::mem::take(&mut writer.links);
+                writer.layout.lines.join(" ")
+            })
+            .collect()
+    }
+}
+
+impl<'w> TextWriter<'w> for Writer<'w> {
+    fn layout(&mut self) -> &mut TextLayout<'w> {
+        &mut self.layout
+    }
+
+    /// Wrap the current line into the width left after the indent
+    fn flush(&mut self) {
+        let line = std::mem::take(&mut self.layout.line);
+        let width = self
+            .width
+            .saturating_sub(self.layout.indent.chars().count());
+        for wrapped in wrap(&line, width) {
+            self.layout.push_line(&wrapped);
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        self.layout.line.push_str(text);
+    }
+
+    fn element(&mut self, entity: Entity, tag: &'static str) -> bool {
+        match tag {
+            "br" => self.flush(),
+            "hr" => {
+                self.blank_line();
+                self.layout.push_line(&"-".repeat(self.width));
+                self.blank_line();
+            }
+            "h1" | "h2" => {
+                self.blank_line();
+                self.render_children(entity);
+                self.flush();
+                let underline = if tag == "h1" { "=" } else { "-" };
+                let indent = self.layout.indent.chars().count();
+                let width = self
+                    .layout
+                    .lines
+                    .last()
+                    .map(|l| l.chars().count().saturating_sub(indent))
+                    .unwrap_or_default();
+                self.layout.push_line(&underline.repeat(width));
+                self.blank_line();
+            }
+            "p" | "h3" | "h4" | "h5" | "h6" => {
+                self.blank_line();
+                self.render_children(entity);
+                self.blank_line();
+            }
+            "a" => self.link(entity),
+            "img" => {
+                if let Some(alt) = self
+                    .layout
+                    .attribute(entity, "alt")
+                    .filter(|a| !a.is_empty())
+                {
+                    self.layout.line.push_str(&format!("[{}]", alt));
+                }
+            }
+            "pre" => {
+                self.blank_line();
+                for line in raw_text(self.layout.world, entity).trim_end().lines() {
+                    self.layout.push_line(line);
+                }
+                self.blank_line();
+            }
+            "ul" | "ol" | "menu" => self.list(entity, tag == "ol", "- "),
+            "blockquote" => {
+                self.blank_line();
+                self.indented(entity, "> ");
+                self.blank_line();
+            }
+            "table" => self.table(entity),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Collapse whitespace and break text into lines no longer than width.
+/// Words longer than the width are left on their own line
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let length = line.chars().count();
+        if length > 0 && length + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
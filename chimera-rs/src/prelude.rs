@@ -1,6 +1,6 @@
 pub use crate::html::*;
 pub use crate::logic::*;
 pub use crate::node::*;
-pub use crate::r#async::AsyncTasks;
+pub use crate::r#async::{AsyncError, AsyncTasks, ErrorBoundary, TimedOut, TimeoutBoundary};
 pub use chimera_rs_macros::*;
 pub use map_macro::*;
@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+
+use crate::{
+    html::tag::tag_systems,
+    text_layout::{roots, TextLayout, TextWriter},
+};
+
+const BOLD: (&str, &str) = ("\x1b[1m", "\x1b[22m");
+const ITALIC: (&str, &str) = ("\x1b[3m", "\x1b[23m");
+const UNDERLINE: (&str, &str) = ("\x1b[4m", "\x1b[24m");
+
+/// How the terminal output is laid out
+#[derive(Resource, Debug, Clone)]
+pub struct TerminalOptions {
+    /// Width of horizontal rules
+    pub width: usize,
+    /// Whether to use ansi escapes for bold, italic and underlined text.
+    /// Turn off for plain output, such as for snapshots
+    pub ansi: bool,
+}
+
+impl Default for TerminalOptions {
+    fn default() -> Self {
+        Self {
+            width: 80,
+            ansi: true,
+        }
+    }
+}
+
+/// The entity tree laid out for a terminal, rebuilt every update
+#[derive(Resource, Debug, Default, Clone)]
+pub struct TerminalOutput(pub String);
+
+#[derive(Debug, Hash, Eq, PartialEq, Clone, SystemSet)]
+enum TerminalRenderSet {
+    ApplyTags,
+    Render,
+}
+
+/// Renders the same entity trees as RenderHtmlPlugin as a terminal layout into TerminalOutput.
+/// Block elements go on their own lines, lists get bullets, tables get aligned columns,
+/// and bold, italic and link text is styled with ansi escapes
+pub struct RenderTerminalPlugin;
+impl Plugin for RenderTerminalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, tag_systems().in_set(TerminalRenderSet::ApplyTags));
+        app.add_systems(
+            PostUpdate,
+            render_terminal_output.in_set(TerminalRenderSet::Render),
+        );
+        app.init_resource::<TerminalOptions>();
+        app.init_resource::<TerminalOutput>();
+    }
+}
+
+fn render_terminal_output(world: &mut World) {
+    let output = render_terminal(world);
+    world.resource_mut::<TerminalOutput>().0 = output;
+}
+
+/// Lay out every root entity for a terminal, in entity id order
+pub fn render_terminal(world: &mut World) -> String {
+    let roots = roots(world);
+    let options = world
+        .get_resource::<TerminalOptions>()
+        .cloned()
+        .unwrap_or_default();
+    let mut writer = Writer::new(world, &options);
+    for root in roots {
+        writer.entity(root);
+    }
+    writer.finish()
+}
+
+struct Writer<'w> {
+    layout: TextLayout<'w>,
+    options: &'w TerminalOptions,
+    bold: usize,
+    italic: usize,
+    underline: usize,
+}
+
+impl<'w> Writer<'w> {
+    fn new(world: &'w World, options: &'w TerminalOptions) -> Self {
+        Self {
+            layout: TextLayout::new(world),
+            options,
+            bold: 0,
+            italic: 0,
+            underline: 0,
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        self.layout.into_string()
+    }
+
+    /// Wrap inline content in an ansi style, keeping count so nested styles don't end early
+    fn styled(&mut self, entity: Entity, style: (&str, &str), depth: fn(&mut Self) -> &mut usize) {
+        let ansi = self.options.ansi;
+        *depth(self) += 1;
+        if ansi && *depth(self) == 1 {
+            self.layout.line.push_str(style.0);
+        }
+        self.render_children(entity);
+        *depth(self) -= 1;
+        if ansi && *depth(self) == 0 {
+            self.layout.line.push_str(style.1);
+        }
+    }
+
+    /// Lay out rows of cells in columns, padded to the widest cell of each
+    fn table(&mut self, entity: Entity) {
+        self.flush();
+        let rows = self
+            .layout
+            .rows(entity)
+            .into_iter()
+            .map(|row| self.row(row))
+            .collect::<Vec<_>>();
+        let columns = rows.iter().map(|r| r.len()).max().unwrap_or_default();
+        let widths = (0..columns)
+            .map(|c| {
+                rows.iter()
+                    .filter_map(|r| r.get(c))
+                    .map(|cell| visible_width(cell))
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        for row in rows {
+            let cells = row
+                .iter()
+                .enumerate()
+                .map(|(c, cell)| {
+                    let padding = widths[c] - visible_width(cell);
+                    format!("{}{}", cell, " ".repeat(padding))
+                })
+                .collect::<Vec<_>>();
+            self.layout.line = cells.join("  ");
+            self.flush();
+        }
+    }
+
+    /// A row's cells, each laid out on a single line
+    fn row(&self, row: Entity) -> Vec<String> {
+        self.layout
+            .cells(row)
+            .into_iter()
+            .map(|cell| {
+                let mut writer = Writer::new(self.layout.world, self.options);
+                writer.entity(cell);
+                writer.finish().replace('\n', " ")
+            })
+            .collect()
+    }
+}
+
+impl<'w> TextWriter<'w> for Writer<'w> {
+    fn layout(&mut self) -> &mut TextLayout<'w> {
+        &mut self.layout
+    }
+
+    fn flush(&mut self) {
+        let line = std::mem::take(&mut self.layout.line);
+        if !line.trim().is_empty() {
+            self.layout.push_line(&line);
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        // A space between elements doesn't start a line
+        if self.layout.line.is_empty() && text.trim().is_empty() {
+            return;
+        }
+        self.layout.line.push_str(text);
+    }
+
+    fn element(&mut self, entity: Entity, tag: &'static str) -> bool {
+        match tag {
+            "br" => self.flush(),
+            "hr" => {
+                self.flush();
+                self.layout.push_line(&"─".repeat(self.options.width));
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.blank_line();
+                self.styled(entity, BOLD, |w| &mut w.bold);
+                self.blank_line();
+            }
+            "p" => {
+                self.blank_line();
+                self.render_children(entity);
+                self.blank_line();
+            }
+            "b" | "strong" | "th" => self.styled(entity, BOLD, |w| &mut w.bold),
+            "i" | "em" | "cite" | "var" => self.styled(entity, ITALIC, |w| &mut w.italic),
+            "a" | "u" | "ins" => self.styled(entity, UNDERLINE, |w| &mut w.underline),
+            "ul" | "ol" | "menu" => self.list(entity, tag == "ol", "• "),
+            "blockquote" => self.indented(entity, "│ "),
+            "table" => self.table(entity),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// The width of text as shown, without ansi escapes
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| *c == 'm');
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
@@ -0,0 +1,206 @@
+use bevy::prelude::*;
+
+use crate::html::{
+    attributes::Attributes,
+    tag::{ElementKind, Tag},
+    Text,
+};
+
+/// Lines of text built up by the plain text, markdown and terminal renderers
+pub(crate) struct TextLayout<'w> {
+    pub(crate) world: &'w World,
+    pub(crate) lines: Vec<String>,
+    /// The line being laid out, until the renderer flushes it
+    pub(crate) line: String,
+    /// Prefix for each line, from list and blockquote nesting
+    pub(crate) indent: String,
+    /// Prefix for the next line instead of indent, holding a list item's bullet
+    pub(crate) marker: Option<String>,
+}
+
+impl<'w> TextLayout<'w> {
+    pub(crate) fn new(world: &'w World) -> Self {
+        Self {
+            world,
+            lines: vec![],
+            line: String::new(),
+            indent: String::new(),
+            marker: None,
+        }
+    }
+
+    /// The laid out lines, without trailing blank ones
+    pub(crate) fn into_string(mut self) -> String {
+        self.trim_blank_lines();
+        self.lines.join("\n")
+    }
+
+    pub(crate) fn push_line(&mut self, line: &str) {
+        let prefix = self.marker.take().unwrap_or_else(|| self.indent.clone());
+        self.lines
+            .push(format!("{}{}", prefix, line).trim_end().to_string());
+    }
+
+    /// Add a blank line, unless the last line is already blank
+    pub(crate) fn separate(&mut self) {
+        if self.lines.last().is_some_and(|l| !is_blank(l)) {
+            self.lines.push(self.indent.trim_end().to_string());
+        }
+    }
+
+    pub(crate) fn trim_blank_lines(&mut self) {
+        while self.lines.last().is_some_and(|l| is_blank(l)) {
+            self.lines.pop();
+        }
+    }
+
+    pub(crate) fn children(&self, entity: Entity) -> Vec<Entity> {
+        self.world
+            .get::<Children>(entity)
+            .map(|c| c.to_vec())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn tag(&self, entity: Entity) -> Option<&'static str> {
+        self.world.get::<Tag>(entity).map(|t| t.0)
+    }
+
+    pub(crate) fn attribute(&self, entity: Entity, name: &str) -> Option<&'w str> {
+        self.world
+            .get::<Attributes>(entity)
+            .and_then(|a| a.0.get(name))
+            .map(|v| v.as_str())
+    }
+
+    /// Rows of a table, including those in thead, tbody and tfoot
+    pub(crate) fn rows(&self, table: Entity) -> Vec<Entity> {
+        self.children(table)
+            .into_iter()
+            .flat_map(|child| match self.tag(child) {
+                Some("thead" | "tbody" | "tfoot") => self.rows(child),
+                Some("tr") => vec![child],
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    /// Cells of a table row
+    pub(crate) fn cells(&self, row: Entity) -> Vec<Entity> {
+        self.children(row)
+            .into_iter()
+            .filter(|c| matches!(self.tag(*c), Some("td" | "th")))
+            .collect()
+    }
+}
+
+/// Walks the entity tree for a text renderer. Renderers write text and the elements they format
+/// themselves, and everything else is laid out by its ElementKind
+pub(crate) trait TextWriter<'w> {
+    fn layout(&mut self) -> &mut TextLayout<'w>;
+
+    /// Write the current line out, if there's anything on it
+    fn flush(&mut self);
+
+    fn text(&mut self, text: &str);
+
+    /// Lay out an element the renderer formats itself, or return false to lay it out by its kind
+    fn element(&mut self, entity: Entity, tag: &'static str) -> bool;
+
+    fn entity(&mut self, entity: Entity) {
+        let world = self.layout().world;
+        if let Some(text) = world.get::<Text>(entity) {
+            self.text(&text.0);
+            return;
+        }
+        let Some(tag) = self.layout().tag(entity) else {
+            return;
+        };
+        match ElementKind::of(tag) {
+            ElementKind::Hidden => {}
+            _ if self.element(entity, tag) => {}
+            ElementKind::Block => {
+                self.flush();
+                self.render_children(entity);
+                self.flush();
+            }
+            ElementKind::Inline => self.render_children(entity),
+        }
+    }
+
+    fn render_children(&mut self, entity: Entity) {
+        for child in self.layout().children(entity) {
+            self.entity(child);
+        }
+    }
+
+    fn blank_line(&mut self) {
+        self.flush();
+        self.layout().separate();
+    }
+
+    /// Render children with a prefix on each of their lines
+    fn indented(&mut self, entity: Entity, prefix: &str) {
+        self.flush();
+        let indent = self.layout().indent.clone();
+        self.layout().indent.push_str(prefix);
+        self.render_children(entity);
+        self.flush();
+        self.layout().trim_blank_lines();
+        self.layout().indent = indent;
+    }
+
+    /// Each list item gets a bullet or number, with its other lines indented to match
+    fn list(&mut self, entity: Entity, ordered: bool, bullet: &str) {
+        self.flush();
+        let layout = self.layout();
+        let items = layout
+            .children(entity)
+            .into_iter()
+            .filter(|c| layout.tag(*c) == Some("li"))
+            .collect::<Vec<_>>();
+        for (index, item) in items.into_iter().enumerate() {
+            let marker = if ordered {
+                format!("{}. ", index + 1)
+            } else {
+                bullet.to_string()
+            };
+            let layout = self.layout();
+            let indent = layout.indent.clone();
+            layout.marker = Some(format!("{}{}", indent, marker));
+            layout.indent.push_str(&" ".repeat(marker.chars().count()));
+            self.render_children(item);
+            self.flush();
+            let layout = self.layout();
+            layout.marker = None;
+            layout.indent = indent;
+        }
+    }
+}
+
+/// Root entities of the tree, in entity id order.
+/// That follows spawn order only until a despawned entity's id is reused
+pub(crate) fn roots(world: &mut World) -> Vec<Entity> {
+    let mut roots = world
+        .query_filtered::<Entity, (Or<(With<Tag>, With<Text>)>, Without<Parent>)>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    roots.sort();
+    roots
+}
+
+/// Whether a line has nothing but its blockquote prefix
+pub(crate) fn is_blank(line: &str) -> bool {
+    line.chars()
+        .all(|c| c == '>' || c == '│' || c.is_whitespace())
+}
+
+/// All text within an entity, as is
+pub(crate) fn raw_text(world: &World, entity: Entity) -> String {
+    if let Some(text) = world.get::<Text>(entity) {
+        return text.0.clone();
+    }
+    world
+        .get::<Children>(entity)
+        .map(|children| children.iter().map(|c| raw_text(world, *c)).collect())
+        .unwrap_or_default()
+}
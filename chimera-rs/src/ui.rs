@@ -0,0 +1,433 @@
+use bevy::hierarchy::HierarchyQueryExt;
+use bevy::prelude::{
+    default, Added, App, AssetServer, Changed, Commands, Entity, IntoSystemConfigs, Or, Parent,
+    Plugin, PostUpdate, Query, Res, Update, With, Without,
+};
+use bevy::render::color::Color;
+use bevy::text::TextStyle;
+use bevy::ui::{
+    self, node_bundles::ButtonBundle, node_bundles::ImageBundle, node_bundles::NodeBundle,
+    node_bundles::TextBundle, BackgroundColor, BorderColor, OverflowAxis, PositionType, UiImage,
+    UiRect, Val,
+};
+
+use crate::html::{
+    attributes::Attributes,
+    css,
+    tag::{tag_systems, ElementKind, Tag},
+    StyleProperty, Styles, Text,
+};
+
+const DEFAULT_FONT_SIZE: f32 = 16.0;
+
+#[derive(Debug, Hash, Eq, PartialEq, Clone, bevy::ecs::schedule::SystemSet)]
+enum UiRenderSet {
+    ApplyTags,
+    AddNodes,
+}
+
+/// Renders the same entity trees as RenderHtmlPlugin to bevy_ui nodes.
+/// Tagged entities become nodes, buttons and images, text becomes text nodes,
+/// and Styles are translated to bevy_ui's Style
+pub struct RenderBevyUiPlugin;
+impl Plugin for RenderBevyUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, tag_systems().in_set(UiRenderSet::ApplyTags));
+        app.add_systems(
+            Update,
+            (add_ui_nodes, add_ui_text)
+                .in_set(UiRenderSet::AddNodes)
+                .after(UiRenderSet::ApplyTags),
+        );
+        // Runs in PostUpdate, once nodes added in Update have been inserted
+        app.add_systems(PostUpdate, apply_ui_styles);
+    }
+}
+
+/// The layout an element gets before its Styles are applied.
+/// Inline elements lay their children out in a row, blocks in a column, and hidden elements
+/// such as script and head aren't shown
+fn base_style(tag: &Tag) -> ui::Style {
+    let kind = ElementKind::of(tag.0);
+    ui::Style {
+        display: if kind == ElementKind::Hidden {
+            ui::Display::None
+        } else {
+            ui::Display::Flex
+        },
+        flex_direction: if kind == ElementKind::Inline {
+            ui::FlexDirection::Row
+        } else {
+            ui::FlexDirection::Column
+        },
+        ..default()
+    }
+}
+
+/// Add bevy_ui node bundles to tagged entities
+fn add_ui_nodes(
+    mut commands: Commands,
+    query: Query<(Entity, &Tag, Option<&Attributes>), Without<ui::Node>>,
+    asset_server: Option<Res<AssetServer>>,
+) {
+    for (entity, tag, attributes) in &query {
+        let style = base_style(tag);
+        let mut entity = commands.entity(entity);
+        match tag.0 {
+            "button" => entity.insert(ButtonBundle { style, ..default() }),
+            "img" => {
+                let src = attributes.and_then(|a| a.0.get("src"));
+                let image = match (src, &asset_server) {
+                    (Some(src), Some(asset_server)) => UiImage::new(asset_server.load(src.clone())),
+                    _ => UiImage::default(),
+                };
+                entity.insert(ImageBundle {
+                    style,
+                    image,
+                    ..default()
+                })
+            }
+            _ => entity.insert(NodeBundle { style, ..default() }),
+        };
+    }
+}
+
+/// Add text bundles to text entities, sized and colored by the nearest ancestor styles setting them
+fn add_ui_text(
+    mut commands: Commands,
+    query: Query<(Entity, &Text), Without<ui::Node>>,
+    parents: Query<&Parent>,
+    styles: Query<&Styles>,
+) {
+    for (entity, text) in &query {
+        let mut text_style = TextStyle {
+            font_size: DEFAULT_FONT_SIZE,
+            ..default()
+        };
+        let inherited = std::iter::once(entity)
+            .chain(parents.iter_ancestors(entity))
+            .filter_map(|e| styles.get(e).ok())
+            .flat_map(|s| s.0.iter().rev());
+        let (mut font_size, mut color) = (None, None);
+        for property in inherited {
+            match property {
+                StyleProperty::FontSize(size) if font_size.is_none() => {
+                    font_size = Some(pixels(size));
+                }
+                StyleProperty::Color(c) if color.is_none() => color = ui_color(c),
+                _ => {}
+            }
+        }
+        if let Some(font_size) = font_size {
+            text_style.font_size = font_size;
+        }
+        if let Some(color) = color {
+            text_style.color = color;
+        }
+        commands
+            .entity(entity)
+            .insert(TextBundle::from_section(text.0.clone(), text_style));
+    }
+}
+
+type StyledNodeFilter = (With<ui::Node>, Or<(Changed<Styles>, Added<ui::Node>)>);
+
+/// Translate Styles into the bevy_ui Style and colors of nodes, whenever they change
+fn apply_ui_styles(
+    mut commands: Commands,
+    query: Query<(Entity, &Tag, &Styles), StyledNodeFilter>,
+) {
+    for (entity, tag, styles) in &query {
+        let mut style = base_style(tag);
+        apply_to_style(styles, &mut style);
+        let mut entity = commands.entity(entity);
+        entity.insert(style);
+        for property in &styles.0 {
+            match property {
+                StyleProperty::BackgroundColor(c) => {
+                    if let Some(c) = ui_color(c) {
+                        entity.insert(BackgroundColor(c));
+                    }
+                }
+                StyleProperty::BorderColor(c) => {
+                    if let Some(c) = ui_color(c) {
+                        entity.insert(BorderColor(c));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl From<&Styles> for ui::Style {
+    fn from(styles: &Styles) -> Self {
+        let mut style = ui::Style::default();
+        apply_to_style(styles, &mut style);
+        style
+    }
+}
+
+/// Set the layout properties of styles on a bevy_ui Style. Properties without a bevy_ui
+/// equivalent, such as hover variants and custom properties, are left out
+pub fn apply_to_style(styles: &Styles, style: &mut ui::Style) {
+    for property in &styles.0 {
+        match property {
+            StyleProperty::Display(display) => {
+                style.display = match display {
+                    css::Display::None => ui::Display::None,
+                    css::Display::Grid | css::Display::InlineGrid => ui::Display::Grid,
+                    _ => ui::Display::Flex,
+                }
+            }
+            StyleProperty::Position(position) => {
+                style.position_type = match position {
+                    css::Position::Absolute | css::Position::Fixed => PositionType::Absolute,
+                    _ => PositionType::Relative,
+                }
+            }
+            StyleProperty::Top(l) => style.top = val(l),
+            StyleProperty::Right(l) => style.right = val(l),
+            StyleProperty::Bottom(l) => style.bottom = val(l),
+            StyleProperty::Left(l) => style.left = val(l),
+            StyleProperty::Width(l) => style.width = val(l),
+            StyleProperty::Height(l) => style.height = val(l),
+            StyleProperty::MinWidth(l) => style.min_width = val(l),
+            StyleProperty::MinHeight(l) => style.min_height = val(l),
+            StyleProperty::MaxWidth(l) => style.max_width = val(l),
+            StyleProperty::MaxHeight(l) => style.max_height = val(l),
+            StyleProperty::Margin(l) => style.margin = UiRect::all(val(l)),
+            StyleProperty::MarginTop(l) => style.margin.top = val(l),
+            StyleProperty::MarginRight(l) => style.margin.right = val(l),
+            StyleProperty::MarginBottom(l) => style.margin.bottom = val(l),
+            StyleProperty::MarginLeft(l) => style.margin.left = val(l),
+            StyleProperty::Padding(l) => style.padding = UiRect::all(val(l)),
+            StyleProperty::PaddingTop(l) => style.padding.top = val(l),
+            StyleProperty::PaddingRight(l) => style.padding.right = val(l),
+            StyleProperty::PaddingBottom(l) => style.padding.bottom = val(l),
+            StyleProperty::PaddingLeft(l) => style.padding.left = val(l),
+            StyleProperty::BorderWidth(l) => style.border = UiRect::all(val(l)),
+            StyleProperty::Overflow(overflow) => {
+                let axis = match overflow {
+                    css::Overflow::Visible => OverflowAxis::Visible,
+                    _ => OverflowAxis::Clip,
+                };
+                style.overflow = ui::Overflow { x: axis, y: axis };
+            }
+            StyleProperty::FlexDirection(direction) => {
+                style.flex_direction = match direction {
+                    css::FlexDirection::Row => ui::FlexDirection::Row,
+                    css::FlexDirection::RowReverse => ui::FlexDirection::RowReverse,
+                    css::FlexDirection::Column => ui::FlexDirection::Column,
+                    css::FlexDirection::ColumnReverse => ui::FlexDirection::ColumnReverse,
+                }
+            }
+            StyleProperty::FlexWrap(wrap) => {
+                style.flex_wrap = match wrap {
+                    css::FlexWrap::NoWrap => ui::FlexWrap::NoWrap,
+                    css::FlexWrap::Wrap => ui::FlexWrap::Wrap,
+                    css::FlexWrap::WrapReverse => ui::FlexWrap::WrapReverse,
+                }
+            }
+            StyleProperty::FlexGrow(grow) => style.flex_grow = *grow,
+            StyleProperty::FlexShrink(shrink) => style.flex_shrink = *shrink,
+            StyleProperty::FlexBasis(l) => style.flex_basis = val(l),
+            StyleProperty::JustifyContent(justify) => {
+                style.justify_content = match justify {
+                    css::JustifyContent::Start => ui::JustifyContent::Start,
+                    css::JustifyContent::End => ui::JustifyContent::End,
+                    css::JustifyContent::FlexStart => ui::JustifyContent::FlexStart,
+                    css::JustifyContent::FlexEnd => ui::JustifyContent::FlexEnd,
+                    css::JustifyContent::Center => ui::JustifyContent::Center,
+                    css::JustifyContent::SpaceBetween => ui::JustifyContent::SpaceBetween,
+                    css::JustifyContent::SpaceAround => ui::JustifyContent::SpaceAround,
+                    css::JustifyContent::SpaceEvenly => ui::JustifyContent::SpaceEvenly,
+                }
+            }
+            StyleProperty::AlignItems(align) => {
+                style.align_items = match align {
+                    css::AlignItems::Start => ui::AlignItems::Start,
+                    css::AlignItems::End => ui::AlignItems::End,
+                    css::AlignItems::FlexStart => ui::AlignItems::FlexStart,
+                    css::AlignItems::FlexEnd => ui::AlignItems::FlexEnd,
+                    css::AlignItems::Center => ui::AlignItems::Center,
+                    css::AlignItems::Baseline => ui::AlignItems::Baseline,
+                    css::AlignItems::Stretch => ui::AlignItems::Stretch,
+                }
+            }
+            StyleProperty::Gap(l) => {
+                style.row_gap = val(l);
+                style.column_gap = val(l);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Em and rem lengths are relative to the default font size
+fn val(length: &css::Length) -> Val {
+    match *length {
+        css::Length::Auto => Val::Auto,
+        css::Length::Px(v) => Val::Px(v),
+        css::Length::Em(v) | css::Length::Rem(v) => Val::Px(v * DEFAULT_FONT_SIZE),
+        css::Length::Percent(v) => Val::Percent(v),
+        css::Length::Vw(v) => Val::Vw(v),
+        css::Length::Vh(v) => Val::Vh(v),
+    }
+}
+
+fn pixels(length: &css::Length) -> f32 {
+    match val(length) {
+        Val::Px(v) => v,
+        _ => DEFAULT_FONT_SIZE,
+    }
+}
+
+/// A bevy color for a css color. Named colors cover the basic css keywords and #hex strings
+fn ui_color(color: &css::Color) -> Option<Color> {
+    match color {
+        css::Color::Hex(hex) => Some(Color::rgb_u8(
+            (hex >> 16) as u8,
+            (hex >> 8) as u8,
+            *hex as u8,
+        )),
+        css::Color::Rgb(r, g, b) => Some(Color::rgb_u8(*r, *g, *b)),
+        css::Color::Rgba(r, g, b, a) => Some(Color::rgba_u8(*r, *g, *b, (a * 255.0) as u8)),
+        css::Color::Named(name) if name.starts_with('#') => Color::hex(name).ok(),
+        css::Color::Named(name) => {
+            let (r, g, b) = match name.as_ref() {
+                "transparent" => return Some(Color::NONE),
+                "black" => (0, 0, 0),
+                "white" => (255, 255, 255),
+                "gray" | "grey" => (128, 128, 128),
+                "silver" => (192, 192, 192),
+                "red" => (255, 0, 0),
+                "maroon" => (128, 0, 0),
+                "green" => (0, 128, 0),
+                "lime" => (0, 255, 0),
+                "blue" => (0, 0, 255),
+                "navy" => (0, 0, 128),
+                "yellow" => (255, 255, 0),
+                "olive" => (128, 128, 0),
+                "orange" => (255, 165, 0),
+                "purple" => (128, 0, 128),
+                "fuchsia" | "magenta" => (255, 0, 255),
+                "aqua" | "cyan" => (0, 255, 255),
+                "teal" => (0, 128, 128),
+                _ => return None,
+            };
+            Some(Color::rgb_u8(r, g, b))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::UVec2;
+    use bevy::prelude::{BuildWorldChildren, Bundle, Camera, Children, Transform};
+    use bevy::render::camera::Viewport;
+    use bevy::ui::{ui_layout_system, UiScale, UiSurface};
+    use bevy::window::{PrimaryWindow, Window, WindowResized, WindowScaleFactorChanged};
+
+    use super::*;
+    use crate::html::{
+        css::{FlexDirection, Length},
+        tag::{Div, Script, Span},
+    };
+
+    /// An app running bevy_ui's layout headlessly, with a window entity and a camera
+    /// with a fixed viewport rather than a windowing backend or renderer
+    fn layout_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(RenderBevyUiPlugin);
+        app.init_resource::<UiSurface>()
+            .init_resource::<UiScale>()
+            .add_event::<WindowResized>()
+            .add_event::<WindowScaleFactorChanged>()
+            .add_systems(PostUpdate, ui_layout_system.after(apply_ui_styles));
+        app.world.spawn((Window::default(), PrimaryWindow));
+        app.world.spawn(Camera {
+            viewport: Some(Viewport {
+                physical_size: UVec2::new(800, 600),
+                ..default()
+            }),
+            ..default()
+        });
+        app
+    }
+
+    fn spawn_and_update(app: &mut App, tag: impl Bundle, styles: Styles) -> Entity {
+        let entity = app
+            .world
+            .spawn((tag, styles))
+            .with_children(|parent| {
+                parent.spawn(Text("hi".to_string()));
+            })
+            .id();
+        app.update();
+        entity
+    }
+
+    #[test]
+    fn nodes_get_their_tag_layout_and_styles() {
+        let mut app = App::new();
+        app.add_plugins(RenderBevyUiPlugin);
+        let div = spawn_and_update(&mut app, Div, Styles::new().width(Length::Px(120.0)));
+        let span = spawn_and_update(&mut app, Span, Styles::new());
+        let row = spawn_and_update(
+            &mut app,
+            Div,
+            Styles::new().flex_direction(FlexDirection::Row),
+        );
+        let script = spawn_and_update(&mut app, Script, Styles::new());
+
+        let style = |entity| app.world.get::<ui::Style>(entity).unwrap().clone();
+        assert!(app.world.get::<ui::Node>(div).is_some());
+        assert_eq!(style(div).width, Val::Px(120.0));
+        assert_eq!(style(div).flex_direction, ui::FlexDirection::Column);
+        assert_eq!(style(span).flex_direction, ui::FlexDirection::Row);
+        assert_eq!(style(row).flex_direction, ui::FlexDirection::Row);
+        assert_eq!(style(script).display, ui::Display::None);
+
+        let text = app.world.get::<Children>(div).unwrap()[0];
+        assert_eq!(
+            app.world.get::<bevy::text::Text>(text).unwrap().sections[0].value,
+            "hi"
+        );
+    }
+
+    #[test]
+    fn layout_runs_headlessly() {
+        let mut app = layout_app();
+        let cell = || {
+            (
+                Span,
+                Styles::new()
+                    .width(Length::Px(50.0))
+                    .height(Length::Px(10.0)),
+            )
+        };
+        let mut cells = vec![];
+        let row = app
+            .world
+            .spawn((
+                Div,
+                Styles::new()
+                    .width(Length::Px(120.0))
+                    .flex_direction(FlexDirection::Row),
+            ))
+            .with_children(|parent| {
+                cells.push(parent.spawn(cell()).id());
+                cells.push(parent.spawn(cell()).id());
+            })
+            .id();
+        app.update();
+
+        let size = |entity| app.world.get::<ui::Node>(entity).unwrap().size();
+        let x = |entity| app.world.get::<Transform>(entity).unwrap().translation.x;
+        assert_eq!(size(row).x, 120.0);
+        assert_eq!(size(cells[0]).x, 50.0);
+        assert_eq!(size(cells[1]).x, 50.0);
+        assert_eq!(x(cells[1]) - x(cells[0]), 50.0);
+    }
+}
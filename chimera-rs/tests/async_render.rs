@@ -0,0 +1,187 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use chimera_rs::{html::plugin::RenderHtmlPlugin, prelude::*};
+
+/// Holds its entity's render back for a number of polls
+#[derive(Component, Clone)]
+struct Delay(u32);
+
+struct Yield(u32);
+
+impl Future for Yield {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 == 0 {
+            return Poll::Ready(());
+        }
+        self.0 -= 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+fn start_delays(delays: Query<(Entity, &Delay), Added<Delay>>, mut tasks: ResMut<AsyncTasks>) {
+    for (entity, Delay(polls)) in &delays {
+        let polls = *polls;
+        tasks.run_async(entity, move |_| Yield(polls));
+    }
+}
+
+/// Fails its entity's task with the given error
+#[derive(Component, Clone)]
+struct Fail(&'static str);
+
+fn start_failures(failures: Query<(Entity, &Fail), Added<Fail>>, mut tasks: ResMut<AsyncTasks>) {
+    for (entity, Fail(error)) in &failures {
+        let error = *error;
+        tasks.try_run_async(entity, move |_| async move { Err(error) });
+    }
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(RenderHtmlPlugin);
+    app.add_systems(PreUpdate, (start_delays, start_failures));
+    app
+}
+
+#[test]
+fn nested_suspense_is_swapped_in() {
+    let mut app = app();
+    chimera!(
+        <div Suspense("outer".to_string()) Delay(1)>
+            <span>ready</span>
+            <div Suspense("inner".to_string())><p Delay(5)>done</p></div>
+        </div>
+    )
+    .spawn_with_world(&mut app.world);
+    let html = render_app_to_string(&mut app);
+    assert_eq!(html.matches("<template").count(), 2);
+    assert!(html.contains("<template id=\"chimera-suspense-3v1-content\"><div><p>done</p></div>"));
+}
+
+fn error_fallback(error: &AsyncError) -> AnyChimeraNode {
+    let error = error.0.clone();
+    chimera!(<p>Failed: {error}</p>).into()
+}
+
+#[test]
+fn error_boundary_replaces_failed_content() {
+    let mut app = app();
+    chimera!(<div ErrorBoundary(error_fallback)><span Fail("oops")>content</span></div>)
+        .spawn_with_world(&mut app.world);
+    assert_eq!(
+        render_app_to_string(&mut app),
+        "<div><p>Failed: oops</p></div>"
+    );
+}
+
+#[test]
+fn error_without_boundary_still_renders() {
+    let mut app = app();
+    chimera!(<div><span Fail("oops")>content</span></div>).spawn_with_world(&mut app.world);
+    assert_eq!(
+        render_app_to_string(&mut app),
+        "<div><span>content</span></div>"
+    );
+}
+
+/// World callbacks that have been run, in order
+#[derive(Resource, Default)]
+struct Calls(Vec<&'static str>);
+
+#[derive(Component, Clone)]
+struct Callbacks;
+
+fn start_callbacks(callbacks: Query<Entity, Added<Callbacks>>, mut tasks: ResMut<AsyncTasks>) {
+    for entity in &callbacks {
+        tasks.run_async(entity, move |cb| async move {
+            let dropped = cb.with_world(|world| world.resource_mut::<Calls>().0.push("dropped"));
+            drop(dropped);
+            let dropped = cb.with_world_returning(|world| {
+                world.resource_mut::<Calls>().0.push("dropped returning")
+            });
+            drop(dropped);
+            cb.with_world(|world| world.resource_mut::<Calls>().0.push("awaited"))
+                .await;
+        });
+    }
+}
+
+#[test]
+fn with_world_is_sent_once_awaited() {
+    let mut app = app();
+    app.init_resource::<Calls>();
+    app.add_systems(PreUpdate, start_callbacks);
+    chimera!(<p Callbacks>done</p>).spawn_with_world(&mut app.world);
+    assert_eq!(render_app_to_string(&mut app), "<p>done</p>");
+    assert_eq!(app.world.resource::<Calls>().0, vec!["awaited"]);
+}
+
+/// Runs until it times out
+#[derive(Component, Clone)]
+struct Hang;
+
+fn start_hangs(hangs: Query<Entity, Added<Hang>>, mut tasks: ResMut<AsyncTasks>) {
+    for entity in &hangs {
+        tasks.run_async_with_timeout(entity, Some(Duration::from_millis(200)), |_| {
+            Yield(u32::MAX)
+        });
+    }
+}
+
+fn timeout_fallback() -> AnyChimeraNode {
+    chimera!(<p>Timed out</p>).into()
+}
+
+#[test]
+fn timeout_boundary_without_runtime() {
+    let mut app = app();
+    app.add_systems(PreUpdate, start_hangs);
+    chimera!(<div TimeoutBoundary(timeout_fallback)><span Hang>content</span></div>)
+        .spawn_with_world(&mut app.world);
+    assert_eq!(
+        render_app_to_string(&mut app),
+        "<div><p>Timed out</p></div>"
+    );
+}
+
+/// Renders its entity once a tokio timer fires
+#[derive(Component, Clone)]
+struct Sleep(u64);
+
+fn start_sleeps(sleeps: Query<(Entity, &Sleep), Added<Sleep>>, mut tasks: ResMut<AsyncTasks>) {
+    for (entity, Sleep(millis)) in &sleeps {
+        let millis = *millis;
+        tasks.run_async(entity, move |_| {
+            tokio::time::sleep(Duration::from_millis(millis))
+        });
+    }
+}
+
+fn sleeping_app() -> App {
+    let mut app = app();
+    app.add_systems(PreUpdate, start_sleeps);
+    chimera!(<div Suspense("loading".to_string())><p Sleep(10)>slept</p></div>)
+        .spawn_with_world(&mut app.world);
+    app
+}
+
+#[test]
+fn tasks_can_use_tokio_timers() {
+    let html = render_app_to_string(&mut sleeping_app());
+    assert!(html.contains("<p>slept</p>"));
+}
+
+#[tokio::test]
+async fn renders_inside_a_current_thread_runtime() {
+    let html = render_app_to_string(&mut sleeping_app());
+    assert!(html.contains("<p>slept</p>"));
+}
@@ -0,0 +1,177 @@
+use chimera_rs::prelude::*;
+
+#[test]
+fn text_interpolates_expressions() {
+    let duration = 3;
+    let ms = 12;
+    assert_eq!(
+        render_to_string(chimera!(<h1>Slept for {duration} seconds</h1>)),
+        "<h1>Slept for 3 seconds</h1>"
+    );
+    assert_eq!(
+        render_to_string(chimera!(<p>Done (took {ms} ms) [{duration}]</p>)),
+        "<p>Done (took 12 ms) [3]</p>"
+    );
+}
+
+#[test]
+fn text_keeps_spaces_around_inline_elements() {
+    let name = "Bob";
+    assert_eq!(
+        render_to_string(chimera!(<p>Hi <b>{name}</b> there</p>)),
+        "<p>Hi <b>Bob</b> there</p>"
+    );
+    assert_eq!(
+        render_to_string(chimera!(<p>See <a href="/docs">the docs</a>, or <em>ask</em>.</p>)),
+        "<p>See <a href=\"/docs\">the docs</a>, or <em>ask</em>.</p>"
+    );
+    assert_eq!(
+        render_to_string(chimera!(<ul><li>a</li> <li>b</li></ul>)),
+        "<ul><li>a</li><li>b</li></ul>"
+    );
+}
+
+fn sign(n: i32) -> String {
+    render_to_string(chimera!(
+        <p>
+            #if n > 0 { positive }
+            #else if n < 0 { negative }
+            #else { zero }
+        </p>
+    ))
+}
+
+#[test]
+fn if_spawns_one_branch() {
+    assert_eq!(sign(1), "<p>positive</p>");
+    assert_eq!(sign(-1), "<p>negative</p>");
+    assert_eq!(sign(0), "<p>zero</p>");
+}
+
+#[test]
+fn for_spawns_each_item() {
+    let items = vec!["a", "b", "c"];
+    assert_eq!(
+        render_to_string(chimera!(<ul>#for item in items { <li>{item}</li> }</ul>)),
+        "<ul><li>a</li><li>b</li><li>c</li></ul>"
+    );
+}
+
+#[test]
+fn for_items_can_be_used_in_if_branches() {
+    let items = vec!["a".to_string(), "bb".to_string(), "ccc".to_string()];
+    assert_eq!(
+        render_to_string(chimera!(
+            <ul>#for item in items { #if item.len() > 1 { <li>{item}</li> } }</ul>
+        )),
+        "<ul><li>bb</li><li>ccc</li></ul>"
+    );
+}
+
+enum Load {
+    Loading,
+    Ready(u32),
+    Failed(&'static str),
+}
+
+fn load(state: Load) -> String {
+    render_to_string(chimera!(
+        <div>
+            #match state {
+                Load::Loading => { loading }
+                Load::Ready(count) => { <span>{count}</span> }
+                Load::Failed(error) => { <em>{error}</em> }
+            }
+        </div>
+    ))
+}
+
+#[test]
+fn match_spawns_the_matching_arm() {
+    assert_eq!(load(Load::Loading), "<div>loading</div>");
+    assert_eq!(load(Load::Ready(4)), "<div><span>4</span></div>");
+    assert_eq!(load(Load::Failed("oops")), "<div><em>oops</em></div>");
+}
+
+#[test]
+fn text_and_attributes_are_escaped() {
+    let name = "<script>&\"'";
+    assert_eq!(
+        render_to_string(chimera!(<p title={name}>{name}</p>)),
+        "<p title=\"&lt;script&gt;&amp;&quot;&#39;\">&lt;script&gt;&amp;\"'</p>"
+    );
+    assert_eq!(
+        render_to_string(chimera!(<div RawHtml("<b>trusted</b>".to_string())></div>)),
+        "<div><b>trusted</b></div>"
+    );
+}
+
+#[test]
+fn attributes_take_literals_and_expressions() {
+    let id = 7;
+    assert_eq!(
+        render_to_string(chimera!(<a href="/x" data-id={id} aria-label="go">x</a>)),
+        "<a href=\"/x\" data-id=\"7\" aria-label=\"go\">x</a>"
+    );
+}
+
+struct Card {
+    title: String,
+}
+
+impl ChimeraComponent for Card {
+    fn render(self, children: Vec<AnyChimeraNode>) -> AnyChimeraNode {
+        let title = self.title;
+        chimera!(<section><h2>{title}</h2>#{children}</section>).into()
+    }
+}
+
+#[test]
+fn components_render_with_their_children() {
+    assert_eq!(
+        render_to_string(chimera!(<Card title="Hi"><p>body</p></Card>)),
+        "<section><h2>Hi</h2><p>body</p></section>"
+    );
+}
+
+#[test]
+fn fragments_and_roots_render_in_order() {
+    assert_eq!(
+        render_to_string(chimera!(<div><>one<b>two</b></>three</div>)),
+        "<div>one<b>two</b>three</div>"
+    );
+    assert_eq!(
+        render_to_string(chimera!(<p>first</p>{"between"}<p>second</p>)),
+        "<p>first</p>between<p>second</p>"
+    );
+}
+
+#[test]
+fn classes_merge_with_the_class_attribute() {
+    let active = true;
+    let hidden = false;
+    assert_eq!(
+        render_to_string(chimera!(
+            <li class="item" class:active={active} class:hidden={hidden}>x</li>
+        )),
+        "<li class=\"item active\">x</li>"
+    );
+    let classes = Classes::default().with("a").with_if("b", true);
+    assert_eq!(
+        render_to_string(chimera!(<li Classes(classes.0.clone())>x</li>)),
+        "<li class=\"a b\">x</li>"
+    );
+}
+
+#[test]
+fn attributes_keep_their_order() {
+    let styles = Styles::new().color("red");
+    for _ in 0..5 {
+        assert_eq!(
+            render_to_string(chimera!(
+                <a z="1" href="/" class="c" a="2" style="margin:0" Styles(styles.0.clone())>x</a>
+            )),
+            "<a z=\"1\" href=\"/\" class=\"c\" a=\"2\" style=\"margin:0;color:red;\">x</a>"
+        );
+    }
+}
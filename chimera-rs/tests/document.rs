@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+use chimera_rs::{html::plugin::RenderHtmlPlugin, prelude::*};
+
+fn render_document(node: impl Into<AnyChimeraNode>) -> String {
+    let mut app = App::new();
+    app.add_plugins(RenderHtmlPlugin);
+    app.insert_resource(RenderMode::Document);
+    node.into().spawn_with_world(&mut app.world);
+    render_app_to_string(&mut app)
+}
+
+#[test]
+fn head_content_is_hoisted() {
+    assert_eq!(
+        render_document(chimera!(<article><title>Page</title><p>Hi</p></article>)),
+        "<!DOCTYPE html><html><head><title>Page</title></head><body><article><p>Hi</p></article></body></html>"
+    );
+}
+
+#[test]
+fn svg_titles_stay_in_place() {
+    assert_eq!(
+        render_document(chimera!(<div><svg><title>Icon</title></svg></div>)),
+        "<!DOCTYPE html><html><head></head><body><div><svg><title>Icon</title></svg></div></body></html>"
+    );
+}
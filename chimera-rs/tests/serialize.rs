@@ -0,0 +1,54 @@
+use chimera_rs::prelude::*;
+
+#[test]
+fn void_elements_need_no_close_tag() {
+    assert_eq!(
+        render_to_string(chimera!(<p>one<br>two<br/>three<img src="a.png"></p>)),
+        "<p>one<br>two<br>three<img src=\"a.png\"></p>"
+    );
+    assert_eq!(
+        render_to_string(chimera!(<div><hr><hr><hr><hr><hr><hr><hr><hr><hr><hr><hr><hr></div>)),
+        format!("<div>{}</div>", "<hr>".repeat(12))
+    );
+}
+
+#[test]
+fn void_elements_drop_content() {
+    assert_eq!(
+        render_to_string(chimera!(<div><br RawHtml("ignored".to_string())>kept</div>)),
+        "<div><br>kept</div>"
+    );
+}
+
+#[test]
+fn boolean_attributes_render_bare() {
+    let checked = false;
+    assert_eq!(
+        render_to_string(chimera!(<input type="checkbox" disabled checked={checked}>)),
+        "<input type=\"checkbox\" disabled>"
+    );
+    assert_eq!(
+        render_to_string(chimera!(<button hidden="true" data-x="1">go</button>)),
+        "<button hidden data-x=\"1\">go</button>"
+    );
+    assert_eq!(
+        render_to_string(chimera!(<input disabled>)),
+        "<input disabled>"
+    );
+    assert_eq!(
+        render_to_string(chimera!(<input type="checkbox" checked />)),
+        "<input type=\"checkbox\" checked>"
+    );
+    assert_eq!(
+        render_to_string(chimera!(<details open><summary>more</summary></details>)),
+        "<details open><summary>more</summary></details>"
+    );
+}
+
+#[test]
+fn empty_elements_are_closed() {
+    assert_eq!(
+        render_to_string(chimera!(<div><p/><textarea></textarea><script/></div>)),
+        "<div><p></p><textarea></textarea><script></script></div>"
+    );
+}
@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use chimera_rs::{html::plugin::RenderHtmlPlugin, prelude::*};
+
+fn render_stylesheet(node: impl Into<AnyChimeraNode>) -> String {
+    let mut app = App::new();
+    app.add_plugins(RenderHtmlPlugin);
+    app.insert_resource(StyleMode::Stylesheet);
+    node.into().spawn_with_world(&mut app.world);
+    render_app_to_string(&mut app)
+}
+
+#[test]
+fn values_cant_end_the_style_block() {
+    let styles = Styles::new()
+        .color("red}</style><script>alert(1)</script><style>".to_string())
+        .custom("x;y", "1;z:2")
+        .media("(min-width:1px){}", Styles::new().opacity(0.5_f32));
+    let html = render_stylesheet(chimera!(<div Styles(styles.0.clone())>x</div>));
+    assert!(!html.contains("<script>"));
+    assert_eq!(html.matches("</style>").count(), 1);
+    assert!(html.contains("color:red\\7d \\3c /style>\\3c script>"));
+    assert!(html.contains("x\\3b y:1\\3b z:2;"));
+    assert!(html.contains("@media (min-width:1px)\\7b \\7d {"));
+}
+
+#[test]
+fn inline_values_cant_add_declarations() {
+    let styles = Styles::new().custom("color", "red;position:fixed");
+    assert_eq!(
+        render_to_string(chimera!(<div Styles(styles.0.clone())>x</div>)),
+        "<div style=\"color:red\\3b position:fixed;\">x</div>"
+    );
+}
+
+#[test]
+fn inline_styles_follow_a_style_attribute() {
+    let styles = Styles::new().color("red");
+    assert_eq!(
+        render_to_string(chimera!(<div style="margin:0" Styles(styles.0.clone())>x</div>)),
+        "<div style=\"margin:0;color:red;\">x</div>"
+    );
+    assert_eq!(
+        render_to_string(chimera!(<div style="margin:0;" Styles(styles.0.clone())>x</div>)),
+        "<div style=\"margin:0;color:red;\">x</div>"
+    );
+}
@@ -0,0 +1,196 @@
+use bevy::prelude::*;
+use chimera_rs::{
+    markdown::{MarkdownOutput, RenderMarkdownPlugin},
+    plain_text::{PlainTextOptions, PlainTextOutput, RenderPlainTextPlugin},
+    prelude::*,
+    terminal::{RenderTerminalPlugin, TerminalOptions, TerminalOutput},
+};
+
+fn page() -> AnyChimeraNode {
+    chimera!(
+        <h1>Report</h1>
+        <p>
+            Read <a href="https://example.com/docs">the docs</a> or
+            <a href="https://example.com">{"https://example.com"}</a>.
+        </p>
+        <ul><li>one</li><li>two<ol><li>a</li><li>b</li></ol></li></ul>
+        <table>
+            <thead><tr><th>Name</th><th>Qty</th></tr></thead>
+            <tbody><tr><td>apples</td><td>3</td></tr><tr><td>kiwi</td><td>12</td></tr></tbody>
+        </table>
+        <pre><code class="language-rust">{"fn main() {\n    run();\n}"}</code></pre>
+        <script>{"ignored()"}</script>
+    )
+    .into()
+}
+
+fn render(plugin: impl Plugin, setup: impl FnOnce(&mut App)) -> App {
+    let mut app = App::new();
+    app.add_plugins(plugin);
+    setup(&mut app);
+    page().spawn_with_world(&mut app.world);
+    app.update();
+    app
+}
+
+#[test]
+fn plain_text() {
+    let app = render(RenderPlainTextPlugin, |app| {
+        app.insert_resource(PlainTextOptions { width: 40 });
+    });
+    assert_eq!(
+        app.world.resource::<PlainTextOutput>().0,
+        [
+            "Report",
+            "======",
+            "",
+            "Read the docs [1] or",
+            "https://example.com.",
+            "",
+            "- one",
+            "- two",
+            "  1. a",
+            "  2. b",
+            "",
+            "Name    Qty",
+            "apples  3",
+            "kiwi    12",
+            "",
+            "fn main() {",
+            "    run();",
+            "}",
+            "",
+            "[1] https://example.com/docs",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn markdown() {
+    let app = render(RenderMarkdownPlugin, |_| {});
+    assert_eq!(
+        app.world.resource::<MarkdownOutput>().0,
+        [
+            "# Report",
+            "",
+            "Read [the docs](https://example.com/docs) or [https://example.com](https://example.com).",
+            "",
+            "- one",
+            "- two",
+            "  1. a",
+            "  2. b",
+            "",
+            "| Name | Qty |",
+            "| --- | --- |",
+            "| apples | 3 |",
+            "| kiwi | 12 |",
+            "",
+            "```rust",
+            "fn main() {",
+            "    run();",
+            "}",
+            "```",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn terminal() {
+    let app = render(RenderTerminalPlugin, |app| {
+        app.insert_resource(TerminalOptions {
+            width: 20,
+            ansi: false,
+        });
+    });
+    assert_eq!(
+        app.world.resource::<TerminalOutput>().0,
+        [
+            "Report",
+            "",
+            "Read the docs or https://example.com.",
+            "",
+            "• one",
+            "• two",
+            "  1. a",
+            "  2. b",
+            "Name    Qty",
+            "apples  3",
+            "kiwi    12",
+            "fn main() {",
+            "    run();",
+            "}",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn terminal_styles() {
+    let mut app = App::new();
+    app.add_plugins(RenderTerminalPlugin);
+    chimera!(<p><b>bold<i>both</i></b><a href="/">link</a></p>).spawn_with_world(&mut app.world);
+    app.update();
+    assert_eq!(
+        app.world.resource::<TerminalOutput>().0,
+        "\x1b[1mbold\x1b[3mboth\x1b[23m\x1b[22m\x1b[4mlink\x1b[24m"
+    );
+}
+
+#[test]
+fn terminal_lines_dont_start_with_spaces() {
+    let mut app = App::new();
+    app.add_plugins(RenderTerminalPlugin);
+    chimera!(<div><p>first</p> then <b>bold</b></div>).spawn_with_world(&mut app.world);
+    app.update();
+    assert_eq!(
+        app.world.resource::<TerminalOutput>().0,
+        "first\n\nthen \x1b[1mbold\x1b[22m"
+    );
+}
+
+#[test]
+fn markdown_escaping() {
+    let mut app = App::new();
+    app.add_plugins(RenderMarkdownPlugin);
+    chimera!(
+        <p>{"*stars* and [brackets]"}<code>{" a`b "}</code></p>
+        <div>{"- not a list"}</div>
+    )
+    .spawn_with_world(&mut app.world);
+    app.update();
+    assert_eq!(
+        app.world.resource::<MarkdownOutput>().0,
+        "\\*stars\\* and \\[brackets\\]`` a`b ``\n\n\\- not a list"
+    );
+}
+
+#[test]
+fn blockquotes() {
+    let quote = || chimera!(<blockquote><p>first</p><p>second</p></blockquote>);
+    let mut app = App::new();
+    app.add_plugins((
+        RenderMarkdownPlugin,
+        RenderPlainTextPlugin,
+        RenderTerminalPlugin,
+    ));
+    app.insert_resource(TerminalOptions {
+        width: 20,
+        ansi: false,
+    });
+    quote().spawn_with_world(&mut app.world);
+    app.update();
+    assert_eq!(
+        app.world.resource::<MarkdownOutput>().0,
+        "> first\n>\n> second"
+    );
+    assert_eq!(
+        app.world.resource::<PlainTextOutput>().0,
+        "> first\n>\n> second"
+    );
+    assert_eq!(
+        app.world.resource::<TerminalOutput>().0,
+        "│ first\n│\n│ second"
+    );
+}
@@ -1,6 +1,6 @@
-#![feature(async_closure)]
 use axum::{response::IntoResponse, routing::get, Router};
 use chimera_rs::axum_html::AxumHtmlApp;
+use chimera_rs::html::css;
 use chimera_rs::r#async::WorldCallback;
 use tower_http::services::ServeDir;
 
@@ -33,12 +33,12 @@ pub fn init_page(mut commands: Commands) {
     chimera!(
         <div>
             Hello
-            <div Styles(hash_map! {"color" => "red"})>
+            <div Styles(vec![StyleProperty::Color("red".into())])>
                 Yolo
             </div>
             <div Sleep(3)>
                 #if show_fred {
-                    <div Styles(hash_map! {"color" => "blue"})>
+                    <div Styles(vec![StyleProperty::Color(css::Color::Rgb(0, 0, 255))])>
                         Fred
                     </div>
                 }
@@ -53,7 +53,7 @@ struct Sleep(u64);
 
 fn handle_sleeps(query: Query<(Entity, &Sleep)>, mut async_tasks: ResMut<AsyncTasks>) {
     for (entity, Sleep(duration)) in &query {
-        let duration = duration.clone();
+        let duration = *duration;
         async_tasks.run_async(entity, async move |cb: WorldCallback| {
             tokio::time::sleep(std::time::Duration::from_secs(duration)).await;
             cb.with_world(move |world| {